@@ -0,0 +1,184 @@
+use crate::ArgumentReceiver;
+use std::ffi::{OsStr, OsString};
+use std::fs::File;
+use std::io::{self, Write};
+use std::ops::{Deref, DerefMut};
+use std::path::PathBuf;
+use std::process::{Child, ChildStdin, Command, ExitStatus, Output, Stdio};
+use std::thread::{self, JoinHandle};
+
+/// The input fed to the standard input of a program
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum StdinPayload {
+    /// Bytes written to the program after it is spawned
+    Bytes(Vec<u8>),
+    /// A file the program reads from directly
+    File(PathBuf),
+}
+
+/// A `Command` together with the payload fed to its standard input, as
+/// constructed by [`ArgumentExt::to_command`]. Dereferences to the `Command`,
+/// but spawning through the invocation feeds the payload to the program.
+///
+/// [`ArgumentExt::to_command`]: crate::ArgumentExt::to_command
+#[derive(Debug)]
+pub struct Invocation {
+    command: Command,
+    stdin: Option<StdinPayload>,
+    stdout_redirected: bool,
+    stderr_redirected: bool,
+}
+
+impl Invocation {
+    /// An invocation of `command` without a payload
+    pub fn new(command: Command) -> Self {
+        Invocation {
+            command,
+            stdin: None,
+            stdout_redirected: false,
+            stderr_redirected: false,
+        }
+    }
+
+    /// The command, with all arguments and environment variables
+    pub fn command(&self) -> &Command {
+        &self.command
+    }
+
+    /// The command, for example to set its working directory. Redirect its output
+    /// with [`Invocation::stdout`] and [`Invocation::stderr`] instead, which
+    /// [`Invocation::output`] keeps.
+    pub fn command_mut(&mut self) -> &mut Command {
+        &mut self.command
+    }
+
+    /// Redirects the standard output of the program, as with [`Command::stdout`]
+    pub fn stdout<T: Into<Stdio>>(&mut self, cfg: T) -> &mut Self {
+        self.command.stdout(cfg);
+        self.stdout_redirected = true;
+        self
+    }
+
+    /// Redirects the standard error of the program, as with [`Command::stderr`]
+    pub fn stderr<T: Into<Stdio>>(&mut self, cfg: T) -> &mut Self {
+        self.command.stderr(cfg);
+        self.stderr_redirected = true;
+        self
+    }
+
+    /// The payload fed to the standard input of the program. Setting a payload
+    /// replaces a redirection of the standard input with [`Command::stdin`].
+    pub fn stdin_payload(&self) -> Option<&StdinPayload> {
+        self.stdin.as_ref()
+    }
+
+    /// Spawns the program, and feeds the payload to it from a background thread.
+    /// Errors while writing the payload are ignored, for example when the program
+    /// exits without reading all of it. Use [`Invocation::output`] or
+    /// [`Invocation::status`] to have them reported.
+    pub fn spawn(&mut self) -> io::Result<Child> {
+        self.spawn_writer().map(|(child, _)| child)
+    }
+
+    /// Runs the program to completion, collecting its output as [`Command::output`] does.
+    /// Output that is redirected with [`Invocation::stdout`] or [`Invocation::stderr`]
+    /// is not collected.
+    pub fn output(&mut self) -> io::Result<Output> {
+        if !self.stdout_redirected {
+            self.command.stdout(Stdio::piped());
+        }
+        if !self.stderr_redirected {
+            self.command.stderr(Stdio::piped());
+        }
+        let spawned = self.spawn_writer();
+        // Spawning the program otherwise inherits the output again
+        if !self.stdout_redirected {
+            self.command.stdout(Stdio::inherit());
+        }
+        if !self.stderr_redirected {
+            self.command.stderr(Stdio::inherit());
+        }
+        let (child, writer) = spawned?;
+        let output = child.wait_with_output()?;
+        join_writer(writer)?;
+        Ok(output)
+    }
+
+    /// Runs the program to completion, returning its exit status
+    pub fn status(&mut self) -> io::Result<ExitStatus> {
+        let (mut child, writer) = self.spawn_writer()?;
+        let status = child.wait()?;
+        join_writer(writer)?;
+        Ok(status)
+    }
+
+    fn spawn_writer(&mut self) -> io::Result<(Child, Option<JoinHandle<io::Result<()>>>)> {
+        match &self.stdin {
+            None => Ok((self.command.spawn()?, None)),
+            Some(StdinPayload::File(path)) => {
+                self.command.stdin(File::open(path)?);
+                Ok((self.command.spawn()?, None))
+            }
+            Some(StdinPayload::Bytes(bytes)) => {
+                self.command.stdin(Stdio::piped());
+                let mut child = self.command.spawn()?;
+                let stdin = child.stdin.take().expect("stdin is piped");
+                let bytes = bytes.clone();
+                let writer = thread::spawn(move || write_payload(stdin, &bytes));
+                Ok((child, Some(writer)))
+            }
+        }
+    }
+}
+
+impl Deref for Invocation {
+    type Target = Command;
+
+    fn deref(&self) -> &Command {
+        &self.command
+    }
+}
+
+impl DerefMut for Invocation {
+    fn deref_mut(&mut self) -> &mut Command {
+        &mut self.command
+    }
+}
+
+/// Writes the payload and closes stdin, so that the program sees the end of its input
+fn write_payload(mut stdin: ChildStdin, bytes: &[u8]) -> io::Result<()> {
+    stdin.write_all(bytes)
+}
+
+/// A program may exit without reading all of its input, which is not an error
+fn join_writer(writer: Option<JoinHandle<io::Result<()>>>) -> io::Result<()> {
+    match writer.map(|writer| writer.join().expect("the payload writer panicked")) {
+        Some(Err(e)) if e.kind() != io::ErrorKind::BrokenPipe => Err(e),
+        _ => Ok(()),
+    }
+}
+
+impl ArgumentReceiver<OsString> for Invocation {
+    fn one_arg(&mut self, arg: OsString) {
+        self.command.arg(arg);
+    }
+
+    fn multiple_args<I>(&mut self, args: I)
+    where
+        I: IntoIterator<Item = OsString>,
+    {
+        self.command.args(args);
+    }
+
+    fn set_env(&mut self, key: &OsStr, value: &OsStr) {
+        self.command.env(key, value);
+    }
+
+    fn remove_env(&mut self, key: &OsStr) {
+        self.command.env_remove(key);
+    }
+
+    fn set_stdin(&mut self, payload: StdinPayload) {
+        self.stdin = Some(payload);
+    }
+}
@@ -1,8 +1,17 @@
 #![warn(unused_crate_dependencies, missing_docs)]
 //! A proc macro that generates program configurations for external programs.
 
+mod invocation;
+mod recorder;
+mod schema;
 mod std_impls;
 mod traits;
+mod validate;
+mod version;
 
-pub use std_impls::*;
+pub use invocation::*;
+pub use recorder::*;
+pub use schema::*;
 pub use traits::*;
+pub use validate::*;
+pub use version::*;
@@ -0,0 +1,93 @@
+use crate::{ArgumentReceiver, StdinPayload};
+use std::ffi::{OsStr, OsString};
+
+/// An argument received by an [`ArgumentRecorder`], together with its origin
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct RecordedArgument<ArgType = OsString> {
+    /// The argument as it would be passed to the program
+    pub arg: ArgType,
+    /// Whether the argument was passed with [`ArgumentReceiver::untrusted_arg`]
+    pub untrusted: bool,
+}
+
+/// A structured receiver that records where each argument came from, for
+/// example to audit the arguments supplied from outside the program.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct ArgumentRecorder<ArgType = OsString> {
+    args: Vec<RecordedArgument<ArgType>>,
+    env: Vec<(OsString, Option<OsString>)>,
+    stdin: Option<StdinPayload>,
+}
+
+impl<ArgType> Default for ArgumentRecorder<ArgType> {
+    fn default() -> Self {
+        ArgumentRecorder::new()
+    }
+}
+
+impl<ArgType> ArgumentRecorder<ArgType> {
+    /// A recorder without any arguments
+    pub fn new() -> Self {
+        ArgumentRecorder {
+            args: Vec::new(),
+            env: Vec::new(),
+            stdin: None,
+        }
+    }
+
+    /// All recorded arguments, in order
+    pub fn recorded(&self) -> &[RecordedArgument<ArgType>] {
+        &self.args
+    }
+
+    /// The untrusted arguments, in order
+    pub fn untrusted(&self) -> impl Iterator<Item = &ArgType> {
+        self.args
+            .iter()
+            .filter(|recorded| recorded.untrusted)
+            .map(|recorded| &recorded.arg)
+    }
+
+    /// The changes to the environment in order, `None` for a removed variable
+    pub fn environment(&self) -> &[(OsString, Option<OsString>)] {
+        &self.env
+    }
+
+    /// The payload fed to the standard input of the program
+    pub fn stdin(&self) -> Option<&StdinPayload> {
+        self.stdin.as_ref()
+    }
+
+    /// All arguments in order, dropping their origin
+    pub fn into_args(self) -> Vec<ArgType> {
+        self.args.into_iter().map(|recorded| recorded.arg).collect()
+    }
+}
+
+impl<ArgType> ArgumentReceiver<ArgType> for ArgumentRecorder<ArgType> {
+    fn one_arg(&mut self, arg: ArgType) {
+        self.args.push(RecordedArgument {
+            arg,
+            untrusted: false,
+        });
+    }
+
+    fn untrusted_arg(&mut self, arg: ArgType) {
+        self.args.push(RecordedArgument {
+            arg,
+            untrusted: true,
+        });
+    }
+
+    fn set_env(&mut self, key: &OsStr, value: &OsStr) {
+        self.env.push((key.to_owned(), Some(value.to_owned())));
+    }
+
+    fn remove_env(&mut self, key: &OsStr) {
+        self.env.push((key.to_owned(), None));
+    }
+
+    fn set_stdin(&mut self, payload: StdinPayload) {
+        self.stdin = Some(payload);
+    }
+}
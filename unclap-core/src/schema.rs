@@ -0,0 +1,95 @@
+/// A description of an argument and its fields, for example to generate
+/// documentation or a form for a front-end. Usually derived with `#[derive(Argument)]`.
+pub trait ArgumentSchema {
+    /// Describe the fields or variants of the argument
+    fn schema() -> Schema;
+}
+
+/// The description of a struct or an enum
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Schema {
+    /// The doc comment of the item
+    pub doc: Option<&'static str>,
+    /// The fields of a struct, empty for enums
+    pub fields: Vec<FieldInfo>,
+    /// The variants of an enum, empty for structs
+    pub variants: Vec<VariantInfo>,
+}
+
+/// How a field is passed to the program
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum FieldKind {
+    /// A positional argument
+    Positional,
+    /// A flag name followed by a value, as in `--name value`
+    Named,
+    /// A flag that is either present or absent
+    Flag,
+    /// A flag that is repeated a number of times
+    Count,
+    /// The fields of another argument, listed in [`FieldInfo::flattened`]
+    Flatten,
+    /// A subcommand passed after all other fields, listed in [`FieldInfo::flattened`]
+    Subcommand,
+    /// An environment variable
+    Env,
+    /// The standard input of the program
+    Stdin,
+}
+
+/// The description of a single field
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct FieldInfo {
+    /// The name of the field in Rust, or its index for tuple fields
+    pub name: &'static str,
+    /// The flag name as passed when not flattened into another argument. This is
+    /// the name of the variable for environment variables, and the prefix of the
+    /// derived flag names for flattened fields.
+    pub flag: Option<&'static str>,
+    /// How the field is passed
+    pub kind: FieldKind,
+    /// The Rust type of the value, without the `Option` of optional fields
+    pub value_type: &'static str,
+    /// Whether the field can be absent, as an `Option` can
+    pub optional: bool,
+    /// Whether the field holds a collection that passes several values. The
+    /// payload of stdin and the value of an environment variable are single values.
+    pub repeated: bool,
+    /// The doc comment of the field
+    pub doc: Option<&'static str>,
+    /// The schema of a flattened field, or the variants of a subcommand field
+    pub flattened: Option<Schema>,
+}
+
+/// How the variant of an enum is passed to the program
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum VariantKind {
+    /// Only the fields of the variant are passed
+    Unnamed,
+    /// A flag name followed by the fields of the variant
+    Named,
+    /// A subcommand name followed by the fields of the variant
+    Subcommand,
+}
+
+/// The description of a single variant of an enum
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct VariantInfo {
+    /// The name of the variant in Rust
+    pub name: &'static str,
+    /// The flag name or the subcommand name of the variant
+    pub flag: Option<&'static str>,
+    /// How the variant is passed
+    pub kind: VariantKind,
+    /// The doc comment of the variant
+    pub doc: Option<&'static str>,
+    /// The fields of the variant
+    pub fields: Vec<FieldInfo>,
+}
+
+/// Optional flattened arguments and subcommands have the schema of the argument.
+impl<A: ArgumentSchema> ArgumentSchema for Option<A> {
+    fn schema() -> Schema {
+        A::schema()
+    }
+}
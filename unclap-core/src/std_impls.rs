@@ -1,6 +1,49 @@
-use crate::traits::{Argument, ArgumentReceiver, ArgumentReceiverExt};
+use crate::traits::{Argument, ArgumentReceiver, ArgumentReceiverExt, ArgumentType};
 use std::ffi::{OsStr, OsString};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+impl ArgumentType for OsString {
+    fn from_text(text: &str) -> Self {
+        OsString::from(text)
+    }
+
+    fn push_text(&mut self, text: &str) {
+        self.push(text);
+    }
+
+    fn push_arg(&mut self, arg: Self) {
+        self.push(arg);
+    }
+}
+
+impl ArgumentType for String {
+    fn from_text(text: &str) -> Self {
+        String::from(text)
+    }
+
+    fn push_text(&mut self, text: &str) {
+        self.push_str(text);
+    }
+
+    fn push_arg(&mut self, arg: Self) {
+        self.push_str(&arg);
+    }
+}
+
+/// Byte strings, for example to pass arguments that are not valid unicode without loss.
+impl ArgumentType for Vec<u8> {
+    fn from_text(text: &str) -> Self {
+        text.as_bytes().to_vec()
+    }
+
+    fn push_text(&mut self, text: &str) {
+        self.extend_from_slice(text.as_bytes());
+    }
+
+    fn push_arg(&mut self, mut arg: Self) {
+        self.append(&mut arg);
+    }
+}
 
 impl Argument<OsString> for Path {
     fn append_to<R: ArgumentReceiver<OsString>>(&self, r: &mut R) {
@@ -8,24 +51,97 @@ impl Argument<OsString> for Path {
     }
 }
 
-impl Argument<OsString> for String {
+impl Argument<OsString> for PathBuf {
     fn append_to<R: ArgumentReceiver<OsString>>(&self, r: &mut R) {
         r.arg(self);
     }
 }
 
-impl<'a> Argument<OsString> for &'a str {
+impl<T: ArgumentType> Argument<T> for String {
+    fn append_to<R: ArgumentReceiver<T>>(&self, r: &mut R) {
+        r.one_arg(T::from_text(self));
+    }
+}
+
+impl<T: ArgumentType> Argument<T> for str {
+    fn append_to<R: ArgumentReceiver<T>>(&self, r: &mut R) {
+        r.one_arg(T::from_text(self));
+    }
+}
+
+impl Argument<OsString> for OsString {
     fn append_to<R: ArgumentReceiver<OsString>>(&self, r: &mut R) {
         r.arg(self);
     }
 }
 
-impl<'a> Argument<OsString> for &'a OsStr {
+impl Argument<OsString> for OsStr {
     fn append_to<R: ArgumentReceiver<OsString>>(&self, r: &mut R) {
         r.arg(self);
     }
 }
 
+/// The exact bytes of paths and OS strings, which need not be valid unicode
+#[cfg(unix)]
+mod unix_bytes {
+    use crate::traits::{Argument, ArgumentReceiver};
+    use std::ffi::{OsStr, OsString};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::{Path, PathBuf};
+
+    impl Argument<Vec<u8>> for OsStr {
+        fn append_to<R: ArgumentReceiver<Vec<u8>>>(&self, r: &mut R) {
+            r.one_arg(self.as_bytes().to_vec());
+        }
+    }
+
+    impl Argument<Vec<u8>> for OsString {
+        fn append_to<R: ArgumentReceiver<Vec<u8>>>(&self, r: &mut R) {
+            self.as_os_str().append_to(r);
+        }
+    }
+
+    impl Argument<Vec<u8>> for Path {
+        fn append_to<R: ArgumentReceiver<Vec<u8>>>(&self, r: &mut R) {
+            self.as_os_str().append_to(r);
+        }
+    }
+
+    impl Argument<Vec<u8>> for PathBuf {
+        fn append_to<R: ArgumentReceiver<Vec<u8>>>(&self, r: &mut R) {
+            self.as_os_str().append_to(r);
+        }
+    }
+}
+
+macro_rules! display_argument {
+    ($($ty:ty),*) => {
+        $(
+            impl<T: ArgumentType> Argument<T> for $ty {
+                fn append_to<R: ArgumentReceiver<T>>(&self, r: &mut R) {
+                    r.one_arg(T::from_text(&self.to_string()));
+                }
+            }
+        )*
+    };
+}
+
+display_argument!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+impl<T, A: Argument<T>> Argument<T> for Option<A> {
+    fn append_to<R: ArgumentReceiver<T>>(&self, r: &mut R) {
+        if let Some(arg) = self {
+            arg.append_to(r);
+        }
+    }
+}
+
+impl<T, A: Argument<T> + ?Sized> Argument<T> for &A {
+    fn append_to<R: ArgumentReceiver<T>>(&self, r: &mut R) {
+        (**self).append_to(r);
+    }
+}
+
 impl<T, A: Argument<T>> Argument<T> for std::vec::Vec<A> {
     fn append_to<R: ArgumentReceiver<T>>(&self, r: &mut R) {
         for arg in self {
@@ -34,7 +150,7 @@ impl<T, A: Argument<T>> Argument<T> for std::vec::Vec<A> {
     }
 }
 
-impl<'a, T, A: Argument<T>> Argument<T> for [A] {
+impl<T, A: Argument<T>> Argument<T> for [A] {
     fn append_to<R: ArgumentReceiver<T>>(&self, r: &mut R) {
         for arg in self {
             arg.append_to(r);
@@ -42,7 +158,7 @@ impl<'a, T, A: Argument<T>> Argument<T> for [A] {
     }
 }
 
-impl<'a, T, A: Argument<T>, const N: usize> Argument<T> for [A; N] {
+impl<T, A: Argument<T>, const N: usize> Argument<T> for [A; N] {
     fn append_to<R: ArgumentReceiver<T>>(&self, r: &mut R) {
         for arg in self {
             arg.append_to(r);
@@ -84,3 +200,28 @@ impl<T, A: Argument<T>, B: Argument<T>, C: Argument<T>, D: Argument<T>> Argument
         self.3.append_to(r);
     }
 }
+
+/// Each entry as its key followed by its value, like a sequence of pairs.
+impl<T, K: Argument<T>, V: Argument<T>> Argument<T> for std::collections::BTreeMap<K, V> {
+    fn append_to<R: ArgumentReceiver<T>>(&self, r: &mut R) {
+        for (key, value) in self {
+            key.append_to(r);
+            value.append_to(r);
+        }
+    }
+}
+
+/// Like the `BTreeMap` impl. The entries are sorted by key, so the arguments
+/// do not depend on the iteration order of the map.
+impl<T, K: Argument<T> + Ord, V: Argument<T>, S> Argument<T>
+    for std::collections::HashMap<K, V, S>
+{
+    fn append_to<R: ArgumentReceiver<T>>(&self, r: &mut R) {
+        let mut entries: Vec<_> = self.iter().collect();
+        entries.sort_by_key(|(key, _)| *key);
+        for (key, value) in entries {
+            key.append_to(r);
+            value.append_to(r);
+        }
+    }
+}
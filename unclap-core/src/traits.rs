@@ -1,3 +1,5 @@
+use crate::invocation::{Invocation, StdinPayload};
+use crate::version::{UnsupportedArgument, Version, Versioned};
 use std::ffi::{OsStr, OsString};
 use std::process::Command;
 
@@ -16,6 +18,46 @@ pub trait ArgumentReceiver<ArgType = OsString> {
             self.one_arg(arg);
         }
     }
+    /// Pass a single argument that was supplied from outside the program, such as
+    /// extra arguments from a configuration file. Receivers that keep track of the
+    /// origin of arguments can mark it for auditing, others pass it like [`one_arg`].
+    ///
+    /// [`one_arg`]: ArgumentReceiver::one_arg
+    fn untrusted_arg(&mut self, arg: ArgType) {
+        self.one_arg(arg);
+    }
+    /// Set an environment variable of the program, as with [`Command::env`].
+    /// Receivers that only collect arguments have no environment and ignore it by default.
+    fn set_env(&mut self, key: &OsStr, value: &OsStr) {
+        let _ = (key, value);
+    }
+    /// Remove an environment variable of the program, as with [`Command::env_remove`].
+    /// Receivers that only collect arguments have no environment and ignore it by default.
+    fn remove_env(&mut self, key: &OsStr) {
+        let _ = key;
+    }
+    /// Feed a payload to the standard input of the program once it is spawned.
+    /// Receivers that can not hold on to the payload ignore it by default, including
+    /// `Command`. Use an [`Invocation`] instead, as returned by [`ArgumentExt::to_command`].
+    fn set_stdin(&mut self, payload: StdinPayload) {
+        let _ = payload;
+    }
+    /// The version of the program that the arguments are rendered for, if any.
+    /// Version-gated fields are passed unconditionally without a target version.
+    fn target_version(&self) -> Option<&Version> {
+        None
+    }
+    /// Report a field that is set, but not supported by the [`target_version`].
+    ///
+    /// # Panics
+    ///
+    /// Receivers without a target version are never passed unsupported fields,
+    /// and panic by default.
+    ///
+    /// [`target_version`]: ArgumentReceiver::target_version
+    fn unsupported_arg(&mut self, unsupported: UnsupportedArgument) {
+        panic!("{}", unsupported);
+    }
 }
 
 impl ArgumentReceiver<OsString> for Command {
@@ -29,6 +71,29 @@ impl ArgumentReceiver<OsString> for Command {
     {
         self.args(args);
     }
+
+    fn set_env(&mut self, key: &OsStr, value: &OsStr) {
+        self.env(key, value);
+    }
+
+    fn remove_env(&mut self, key: &OsStr) {
+        self.env_remove(key);
+    }
+}
+
+/// Collects the arguments, for example to post-process them before passing
+/// them on to another receiver.
+impl<ArgType> ArgumentReceiver<ArgType> for Vec<ArgType> {
+    fn one_arg(&mut self, arg: ArgType) {
+        self.push(arg);
+    }
+
+    fn multiple_args<I>(&mut self, args: I)
+    where
+        I: IntoIterator<Item = ArgType>,
+    {
+        self.extend(args);
+    }
 }
 
 /// Extension methods for [`ArgumentReciever`], mirroring [`Command::arg`]
@@ -70,6 +135,17 @@ where
     }
 }
 
+/// Types of single arguments, such as `OsString` for a `Command`, `String` for
+/// arguments that are sent as JSON, or `Vec<u8>` for byte strings.
+pub trait ArgumentType: Sized {
+    /// Convert a string into an argument
+    fn from_text(text: &str) -> Self;
+    /// Append a string to the argument, such as the `=` of `--name=value`
+    fn push_text(&mut self, text: &str);
+    /// Append another argument, such as the value of `--name=value`
+    fn push_arg(&mut self, arg: Self);
+}
+
 /// Things that can act as program arguments such as flags, filenames etc...
 pub trait Argument<ArgType = OsString> {
     /// Append the argument to the command. Generally calls either [`Command::arg`]
@@ -79,16 +155,34 @@ pub trait Argument<ArgType = OsString> {
 
 /// Extension methods for arguments
 pub trait ArgumentExt {
-    /// Construct a new `Command` and give the argument
-    fn to_command<S: AsRef<OsStr>>(&self, program: S) -> Command;
+    /// Construct a new `Command` and give the argument. The command is wrapped in an
+    /// [`Invocation`], which feeds the payload of a `stdin` field to the program.
+    fn to_command<S: AsRef<OsStr>>(&self, program: S) -> Invocation;
+    /// Construct a new `Command` for a specific version of the program and give
+    /// the argument, failing for set fields the version does not support
+    fn to_versioned_command<S: AsRef<OsStr>>(
+        &self,
+        program: S,
+        version: Version,
+    ) -> Result<Invocation, UnsupportedArgument>;
 }
 
 impl<A: Argument<OsString>> ArgumentExt for A {
-    fn to_command<S: AsRef<OsStr>>(&self, program: S) -> Command {
-        let mut cmd = Command::new(program);
+    fn to_command<S: AsRef<OsStr>>(&self, program: S) -> Invocation {
+        let mut cmd = Invocation::new(Command::new(program));
         self.append_to(&mut cmd);
         cmd
     }
+
+    fn to_versioned_command<S: AsRef<OsStr>>(
+        &self,
+        program: S,
+        version: Version,
+    ) -> Result<Invocation, UnsupportedArgument> {
+        let mut cmd = Versioned::new(Invocation::new(Command::new(program)), version);
+        self.append_to(&mut cmd);
+        cmd.finish()
+    }
 }
 
 /// Extension trait for Command, to enable inversion of control for [`Argument::append_to`].
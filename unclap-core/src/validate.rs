@@ -0,0 +1,146 @@
+use crate::{Argument, ArgumentExt, ArgumentReceiver, Invocation};
+use std::ffi::{OsStr, OsString};
+
+/// Arguments whose fields are checked against constraints before they are passed,
+/// such as flags that conflict with each other. Usually derived with `#[derive(Argument)]`.
+pub trait Validate {
+    /// Check all constraints, returning every violation
+    fn validate(&self) -> Result<(), ValidationErrors>;
+
+    /// Check all constraints, and append the argument only if they hold
+    fn append_checked<T, R: ArgumentReceiver<T>>(&self, r: &mut R) -> Result<(), ValidationErrors>
+    where
+        Self: Argument<T>,
+    {
+        self.validate()?;
+        self.append_to(r);
+        Ok(())
+    }
+
+    /// Check all constraints, and construct a new `Command` only if they hold
+    fn to_checked_command<S: AsRef<OsStr>>(
+        &self,
+        program: S,
+    ) -> Result<Invocation, ValidationErrors>
+    where
+        Self: Argument<OsString>,
+    {
+        self.validate()?;
+        Ok(self.to_command(program))
+    }
+}
+
+/// Optional flattened arguments and subcommands are only checked when present.
+impl<A: Validate> Validate for Option<A> {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        match self {
+            Some(arg) => arg.validate(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A constraint on a field that does not hold
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    /// The field is set together with the named field
+    ConflictsWith(&'static str),
+    /// The field is set, but the named field is not
+    Requires(&'static str),
+    /// Not exactly one field of the group is set, the fields that are set are listed
+    OneOf(Vec<&'static str>),
+    /// The value is outside of the inclusive bounds, given as written in the attribute
+    OutOfRange {
+        /// The smallest allowed value
+        min: Option<&'static str>,
+        /// The largest allowed value
+        max: Option<&'static str>,
+    },
+    /// The value is empty
+    Empty,
+    /// The value does not match the regular expression
+    Mismatch(&'static str),
+}
+
+/// A violated constraint of a single field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// The name of the field in Rust, or the name of the group for [`Violation::OneOf`]
+    pub field: &'static str,
+    /// The constraint that does not hold
+    pub violation: Violation,
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.violation {
+            Violation::ConflictsWith(other) => {
+                write!(f, "`{}` can not be used with `{}`", self.field, other)
+            }
+            Violation::Requires(other) => write!(f, "`{}` requires `{}`", self.field, other),
+            Violation::OneOf(set) if set.is_empty() => {
+                write!(f, "One field of the group `{}` has to be set", self.field)
+            }
+            Violation::OneOf(set) => write!(
+                f,
+                "Only one field of the group `{}` can be set, but `{}` are set",
+                self.field,
+                set.join("`, `")
+            ),
+            Violation::OutOfRange { min, max } => {
+                write!(f, "`{}` has to be", self.field)?;
+                match (min, max) {
+                    (Some(min), Some(max)) => write!(f, " between {} and {}", min, max),
+                    (Some(min), None) => write!(f, " at least {}", min),
+                    (None, Some(max)) => write!(f, " at most {}", max),
+                    (None, None) => Ok(()),
+                }
+            }
+            Violation::Empty => write!(f, "`{}` can not be empty", self.field),
+            Violation::Mismatch(pattern) => {
+                write!(f, "`{}` has to match `{}`", self.field, pattern)
+            }
+        }
+    }
+}
+
+/// All violated constraints of an argument, as returned by [`Validate::validate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationErrors {
+    errors: Vec<ValidationError>,
+}
+
+impl ValidationErrors {
+    /// The errors, or nothing if there are none
+    pub fn from_errors(errors: Vec<ValidationError>) -> Result<(), Self> {
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationErrors { errors })
+        }
+    }
+
+    /// The errors in the order of the fields
+    pub fn errors(&self) -> &[ValidationError] {
+        &self.errors
+    }
+
+    /// The errors in the order of the fields
+    pub fn into_errors(self) -> Vec<ValidationError> {
+        self.errors
+    }
+}
+
+impl std::fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (idx, error) in self.errors.iter().enumerate() {
+            if idx > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
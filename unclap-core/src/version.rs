@@ -0,0 +1,194 @@
+use crate::invocation::StdinPayload;
+use crate::ArgumentReceiver;
+use std::cmp::Ordering;
+use std::ffi::OsStr;
+use std::str::FromStr;
+
+/// The version of a program, such as `2.28.1`. Missing trailing parts count
+/// as zero, so `2.28` and `2.28.0` are equal.
+#[derive(Debug, Clone)]
+pub struct Version {
+    parts: Vec<u64>,
+}
+
+impl Version {
+    /// A version from its dot-separated parts
+    pub fn new(parts: &[u64]) -> Self {
+        Version {
+            parts: parts.to_vec(),
+        }
+    }
+
+    /// The dot-separated parts of the version
+    pub fn parts(&self) -> &[u64] {
+        &self.parts
+    }
+
+    /// Whether the version is at least `since` and before `until`
+    pub fn is_within(&self, since: Option<&Version>, until: Option<&Version>) -> bool {
+        since.is_none_or(|since| self >= since) && until.is_none_or(|until| self < until)
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let len = self.parts.len().max(other.parts.len());
+        let part = |parts: &[u64], idx: usize| parts.get(idx).copied().unwrap_or(0);
+        (0..len)
+            .map(|idx| part(&self.parts, idx).cmp(&part(&other.parts, idx)))
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Version {}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (idx, part) in self.parts.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(".")?;
+            }
+            write!(f, "{}", part)?;
+        }
+        Ok(())
+    }
+}
+
+/// The error when parsing a version that is not made of dot-separated numbers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseVersionError {
+    input: String,
+}
+
+impl std::fmt::Display for ParseVersionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Invalid version `{}`, expected dot-separated numbers such as `2.28.1`",
+            self.input
+        )
+    }
+}
+
+impl std::error::Error for ParseVersionError {}
+
+impl FromStr for Version {
+    type Err = ParseVersionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split('.')
+            .map(|part| part.parse())
+            .collect::<Result<_, _>>()
+            .map(|parts| Version { parts })
+            .map_err(|_| ParseVersionError {
+                input: s.to_owned(),
+            })
+    }
+}
+
+/// A field that is set, but not supported by the target version of the program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedArgument {
+    /// The flag name of the field as passed, or the name of the field for positional fields
+    pub name: String,
+    /// The first version supporting the field
+    pub since: Option<Version>,
+    /// The first version no longer supporting the field
+    pub until: Option<Version>,
+    /// The target version
+    pub version: Version,
+}
+
+impl std::fmt::Display for UnsupportedArgument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "`{}` is supported ", self.name)?;
+        match (&self.since, &self.until) {
+            (Some(since), Some(until)) => write!(f, "from version {} until {}", since, until)?,
+            (Some(since), None) => write!(f, "from version {}", since)?,
+            (None, Some(until)) => write!(f, "before version {}", until)?,
+            (None, None) => f.write_str("by no version")?,
+        }
+        write!(f, ", but the target version is {}", self.version)
+    }
+}
+
+impl std::error::Error for UnsupportedArgument {}
+
+/// A receiver rendering arguments for a specific version of the program,
+/// wrapping another receiver. Version-gated fields that are not supported by
+/// the version are reported by [`Versioned::finish`].
+#[derive(Debug)]
+pub struct Versioned<R> {
+    receiver: R,
+    version: Version,
+    unsupported: Option<UnsupportedArgument>,
+}
+
+impl<R> Versioned<R> {
+    /// Render arguments for `version` into `receiver`
+    pub fn new(receiver: R, version: Version) -> Self {
+        Versioned {
+            receiver,
+            version,
+            unsupported: None,
+        }
+    }
+
+    /// The wrapped receiver, or the first field that is not supported
+    pub fn finish(self) -> Result<R, UnsupportedArgument> {
+        match self.unsupported {
+            Some(unsupported) => Err(unsupported),
+            None => Ok(self.receiver),
+        }
+    }
+}
+
+impl<ArgType, R: ArgumentReceiver<ArgType>> ArgumentReceiver<ArgType> for Versioned<R> {
+    fn one_arg(&mut self, arg: ArgType) {
+        self.receiver.one_arg(arg);
+    }
+
+    fn multiple_args<I>(&mut self, args: I)
+    where
+        I: IntoIterator<Item = ArgType>,
+    {
+        self.receiver.multiple_args(args);
+    }
+
+    fn untrusted_arg(&mut self, arg: ArgType) {
+        self.receiver.untrusted_arg(arg);
+    }
+
+    fn set_env(&mut self, key: &OsStr, value: &OsStr) {
+        self.receiver.set_env(key, value);
+    }
+
+    fn remove_env(&mut self, key: &OsStr) {
+        self.receiver.remove_env(key);
+    }
+
+    fn set_stdin(&mut self, payload: StdinPayload) {
+        self.receiver.set_stdin(payload);
+    }
+
+    fn target_version(&self) -> Option<&Version> {
+        Some(&self.version)
+    }
+
+    fn unsupported_arg(&mut self, unsupported: UnsupportedArgument) {
+        self.unsupported.get_or_insert(unsupported);
+    }
+}
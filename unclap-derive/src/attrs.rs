@@ -1,56 +1,653 @@
+use convert_case::Case;
 use darling::{
     util::{Override, SpannedValue},
-    FromField, FromMeta, FromVariant,
+    FromDeriveInput, FromField, FromMeta, FromVariant,
 };
-use syn::LitStr;
+use quote::ToTokens;
+use syn::{Attribute, Expr, ExprLit, Ident, Index, Lit, LitStr, Member, Meta, NestedMeta};
+
+/// The case convention used for flag names derived from identifiers
+#[derive(Clone, Copy)]
+pub struct RenameRule(pub Case);
+
+impl FromMeta for RenameRule {
+    fn from_string(value: &str) -> darling::Result<Self> {
+        let case = match value {
+            "kebab-case" => Case::Kebab,
+            "snake_case" => Case::Snake,
+            "camelCase" => Case::Camel,
+            "PascalCase" => Case::Pascal,
+            "SCREAMING_SNAKE_CASE" => Case::ScreamingSnake,
+            "SCREAMING-KEBAB-CASE" => Case::Cobol,
+            "lowercase" => Case::Flat,
+            "UPPERCASE" => Case::UpperFlat,
+            _ => {
+                return Err(unknown_value(
+                    value,
+                    &[
+                        "kebab-case",
+                        "snake_case",
+                        "camelCase",
+                        "PascalCase",
+                        "SCREAMING_SNAKE_CASE",
+                        "SCREAMING-KEBAB-CASE",
+                        "lowercase",
+                        "UPPERCASE",
+                    ],
+                ))
+            }
+        };
+        Ok(RenameRule(case))
+    }
+}
+
+/// The error for an unknown value, suggesting the most similar of the `expected` values
+fn unknown_value(value: &str, expected: &[&str]) -> darling::Error {
+    unknown_name("value", value, expected)
+}
+
+/// The error for an unknown name of some kind, such as a value or a field,
+/// suggesting the most similar of the `expected` names
+pub fn unknown_name(kind: &str, value: &str, expected: &[&str]) -> darling::Error {
+    let suggestion = expected
+        .iter()
+        .map(|candidate| (strsim::jaro_winkler(value, candidate), candidate))
+        .filter(|(similarity, _)| *similarity > 0.8)
+        .max_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let message = match suggestion {
+        Some((_, candidate)) => {
+            format!(
+                "Unknown {}: `{}`. Did you mean `{}`?",
+                kind, value, candidate
+            )
+        }
+        None => {
+            let expected: Vec<_> = expected.iter().map(|e| format!("`{}`", e)).collect();
+            format!(
+                "Unknown {}: `{}`. Expected one of {}",
+                kind,
+                value,
+                expected.join(", ")
+            )
+        }
+    };
+    darling::Error::custom(message)
+}
+
+/// The text of the `///` doc comments in `attrs`, without the leading space of each line
+pub fn doc_comment(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::NameValue(doc)) if doc.path.is_ident("doc") => match doc.lit {
+                Lit::Str(line) => Some(line.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .map(|line| {
+            line.strip_prefix(' ')
+                .map_or_else(|| line.clone(), str::to_owned)
+        })
+        .collect();
+    let doc = lines.join("\n").trim().to_owned();
+    if doc.is_empty() {
+        None
+    } else {
+        Some(doc)
+    }
+}
+
+#[derive(FromDeriveInput)]
+#[darling(attributes(argument), forward_attrs(doc))]
+pub struct UnclapContainer {
+    pub attrs: Vec<Attribute>,
+    #[darling(default)]
+    pub rename_all: Option<RenameRule>,
+    #[darling(default)]
+    pub prefix: Option<LitStr>,
+    /// The default style of named fields
+    #[darling(default)]
+    pub style: Option<ValueStyle>,
+    /// Leave out fields that are equal to the field of `Default::default()`
+    #[darling(default)]
+    pub omit_defaults: bool,
+    /// Pass all options before the first positional field. A flattened field is
+    /// sorted as a whole among the options, so positional fields of the flattened
+    /// type still come before the options that follow it. Use `order` to place such a
+    /// field explicitly.
+    #[darling(default)]
+    pub options_first: bool,
+    /// The path of the `unclap` crate in generated code, `::unclap` by default
+    #[darling(rename = "crate", default)]
+    pub krate: Option<syn::Path>,
+    /// The type of single arguments, `OsString` by default
+    #[darling(default)]
+    pub arg_type: Option<syn::Type>,
+}
+
+/// Options of a field variant that include the flag name
+pub trait VariantOptions: FromMeta + Default {
+    fn from_name(name: LitStr) -> Self;
+}
+
+/// Accepts the options of a field variant either as a list or as just the flag name,
+/// e.g. `flag`, `flag = "-n"` and `flag(name = "-n", negatable)`.
+pub struct NameOrOptions<T>(pub T);
+
+impl<T: VariantOptions> FromMeta for NameOrOptions<T> {
+    fn from_word() -> darling::Result<Self> {
+        Ok(NameOrOptions(T::default()))
+    }
+
+    fn from_value(value: &Lit) -> darling::Result<Self> {
+        match value {
+            Lit::Str(name) => Ok(NameOrOptions(T::from_name(name.clone()))),
+            _ => Err(darling::Error::unexpected_lit_type(value)),
+        }
+    }
+
+    fn from_list(items: &[NestedMeta]) -> darling::Result<Self> {
+        T::from_list(items).map(NameOrOptions)
+    }
+}
+
+#[derive(FromMeta, Default)]
+#[darling(default)]
+pub struct FlagOptions {
+    pub name: Option<LitStr>,
+    /// Render `Some(false)` as a negative flag, `--no-<name>` unless given explicitly
+    pub negatable: bool,
+    pub negative: Option<LitStr>,
+}
+
+impl VariantOptions for FlagOptions {
+    fn from_name(name: LitStr) -> Self {
+        FlagOptions {
+            name: Some(name),
+            ..FlagOptions::default()
+        }
+    }
+}
+
+/// How the name and the value of a named field are passed
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ValueStyle {
+    /// `--name value`
+    Separate,
+    /// `--name=value`
+    Equals,
+    /// `-Ovalue`
+    Attached,
+    /// `name=value`, derived names have no leading dashes
+    Operand,
+}
+
+impl FromMeta for ValueStyle {
+    fn from_string(value: &str) -> darling::Result<Self> {
+        match value {
+            "separate" => Ok(ValueStyle::Separate),
+            "equals" => Ok(ValueStyle::Equals),
+            "attached" => Ok(ValueStyle::Attached),
+            "operand" => Ok(ValueStyle::Operand),
+            _ => Err(unknown_value(
+                value,
+                &["separate", "equals", "attached", "operand"],
+            )),
+        }
+    }
+}
+
+/// How a named field holding a collection is passed
+#[derive(Default)]
+pub enum Multiple {
+    /// `--name a b c`, using the `Argument` implementation of the collection
+    #[default]
+    Once,
+    /// `--name a --name b --name c`
+    Repeat,
+    /// `--name a,b,c`
+    Delimited(Delimiter),
+}
+
+impl FromMeta for Multiple {
+    fn from_string(value: &str) -> darling::Result<Self> {
+        match value {
+            "once" => Ok(Multiple::Once),
+            "repeat" => Ok(Multiple::Repeat),
+            _ => Err(unknown_value(value, &["once", "repeat"])),
+        }
+    }
+
+    fn from_list(items: &[NestedMeta]) -> darling::Result<Self> {
+        match items {
+            [NestedMeta::Meta(meta)] if meta.path().is_ident("delimited") => {
+                Delimiter::from_meta(meta).map(Multiple::Delimited)
+            }
+            [NestedMeta::Meta(meta)] => Err(darling::Error::unknown_field_with_alts(
+                &darling::util::path_to_string(meta.path()),
+                &["delimited"],
+            )
+            .with_span(meta)),
+            [] => Err(darling::Error::too_few_items(1)),
+            _ => Err(darling::Error::too_many_items(1)),
+        }
+    }
+}
+
+/// The separator of [`Multiple::Delimited`], given as `delimited = ","` or `delimited(",")`
+pub struct Delimiter(pub LitStr);
+
+impl FromMeta for Delimiter {
+    fn from_value(value: &Lit) -> darling::Result<Self> {
+        LitStr::from_value(value).map(Delimiter)
+    }
+
+    fn from_list(items: &[NestedMeta]) -> darling::Result<Self> {
+        match items {
+            [NestedMeta::Lit(lit)] => Self::from_value(lit),
+            _ => Err(darling::Error::too_many_items(1)),
+        }
+    }
+}
+
+#[derive(FromMeta, Default)]
+#[darling(default)]
+pub struct NamedOptions {
+    pub name: Option<LitStr>,
+    /// Defaults to the style of the container
+    pub style: Option<ValueStyle>,
+    pub multiple: Multiple,
+    /// Pass each pair of a map as a single `key=value` argument, joined by this separator
+    pub key_value: Option<LitStr>,
+}
+
+impl VariantOptions for NamedOptions {
+    fn from_name(name: LitStr) -> Self {
+        NamedOptions {
+            name: Some(name),
+            ..NamedOptions::default()
+        }
+    }
+}
+
+#[derive(FromMeta, Default)]
+#[darling(default)]
+pub struct CountOptions {
+    pub name: Option<LitStr>,
+    /// Bundle a repeated short flag into a single argument, e.g. `-vvv`
+    pub bundle: bool,
+    /// The flag repeated for negative counts, e.g. `-q`
+    pub negative: Option<LitStr>,
+}
+
+impl VariantOptions for CountOptions {
+    fn from_name(name: LitStr) -> Self {
+        CountOptions {
+            name: Some(name),
+            ..CountOptions::default()
+        }
+    }
+}
 
 #[derive(FromMeta)]
 #[darling(default)]
 pub enum FieldVariant {
     #[darling(rename = "named")]
-    Named(Override<LitStr>),
+    Named(NameOrOptions<NamedOptions>),
     #[darling(rename = "unnamed")]
-    Unnamed(Override<()>),
+    Unnamed(()),
     #[darling(rename = "flag")]
-    Flag(Override<LitStr>),
+    Flag(NameOrOptions<FlagOptions>),
+    #[darling(rename = "count")]
+    Count(NameOrOptions<CountOptions>),
 }
 
 impl Default for FieldVariant {
     fn default() -> Self {
-        FieldVariant::Unnamed(Override::Inherit)
+        FieldVariant::Unnamed(())
+    }
+}
+
+/// `env = "NAME"` or `env(name = "NAME", remove_empty)`
+#[derive(FromMeta, Default)]
+#[darling(default)]
+pub struct EnvOptions {
+    pub name: Option<LitStr>,
+    /// Remove the variable for a value without arguments such as `None`, instead of
+    /// leaving it untouched
+    pub remove_empty: bool,
+}
+
+impl VariantOptions for EnvOptions {
+    fn from_name(name: LitStr) -> Self {
+        EnvOptions {
+            name: Some(name),
+            ..EnvOptions::default()
+        }
     }
 }
 
+#[derive(FromMeta, Default)]
+pub struct FlattenOptions {
+    #[darling(default)]
+    pub prefix: Option<LitStr>,
+}
+
 #[derive(FromField)]
-#[darling(attributes(argument))]
+#[darling(attributes(argument), forward_attrs(doc))]
 pub struct UnclapField {
     pub ident: Option<syn::Ident>,
     pub ty: syn::Type,
+    pub attrs: Vec<Attribute>,
     #[darling(default)]
     pub variant: SpannedValue<FieldVariant>,
+    #[darling(default)]
+    pub flatten: Option<Override<FlattenOptions>>,
+    /// A function `fn(&T, &mut R)` appending the field to the receiver `R`
+    #[darling(default)]
+    pub with: Option<syn::Path>,
+    /// A template formatting the field into a single value
+    #[darling(default)]
+    pub format: Option<FormatOptions>,
+    /// Never pass the field
+    #[darling(default)]
+    pub skip: bool,
+    /// A predicate `fn(&T) -> bool`, leaving out the field when it holds
+    #[darling(default)]
+    pub skip_if: Option<syn::Path>,
+    /// The default value of the program, leaving out the field when it is equal
+    #[darling(default)]
+    pub default: Option<DefaultValue>,
+    /// Fields are passed in ascending order, and in declaration order for equal orders
+    #[darling(default)]
+    pub order: i32,
+    /// Pass the field after all other fields, separated from them by `--`
+    #[darling(default)]
+    pub last: bool,
+    /// Pass the strings in the field verbatim, as untrusted arguments
+    #[darling(default)]
+    pub raw: bool,
+    /// The field holds a subcommand, passed after all other fields
+    #[darling(default)]
+    pub subcommand: bool,
+    /// Pass the field in this environment variable instead of as arguments
+    #[darling(default)]
+    pub env: Option<NameOrOptions<EnvOptions>>,
+    /// Feed the field to the standard input of the program instead of passing it as arguments
+    #[darling(default)]
+    pub stdin: bool,
+    /// The first version of the program supporting the field
+    #[darling(default)]
+    pub since: Option<VersionLit>,
+    /// The first version of the program no longer supporting the field
+    #[darling(default)]
+    pub until: Option<VersionLit>,
+    /// The flag name passed instead for versions that do not support the field
+    #[darling(default)]
+    pub fallback: Option<LitStr>,
+    /// What to do with a set field that the target version does not support
+    #[darling(default)]
+    pub unsupported: Unsupported,
+    /// Fields that can not be set together with this field
+    #[darling(default)]
+    pub conflicts_with: FieldNames,
+    /// Fields that have to be set whenever this field is set
+    #[darling(default)]
+    pub requires: FieldNames,
+    /// Exactly one field of all fields in this group has to be set
+    #[darling(default)]
+    pub one_of: Option<LitStr>,
+    /// Inclusive bounds of the value
+    #[darling(default)]
+    pub range: Option<RangeOptions>,
+    /// The value can not be empty
+    #[darling(default)]
+    pub non_empty: bool,
+    /// A regular expression that the value has to match
+    #[darling(default)]
+    pub regex: Option<LitStr>,
+}
+
+impl UnclapField {
+    /// Whether the field is passed as a positional argument rather than an option.
+    /// Flattened fields count as options even if they hold positional fields, which
+    /// are not visible when deriving. Environment variables and stdin are neither.
+    pub fn is_positional(&self) -> bool {
+        self.flatten.is_none()
+            && self.env.is_none()
+            && !self.stdin
+            && matches!(*self.variant, FieldVariant::Unnamed(..))
+    }
+
+    /// Replaces the flag name of a flag, count or named field, returning
+    /// whether the field has a flag name
+    pub fn set_flag_name(&mut self, name: LitStr) -> bool {
+        match &mut *self.variant {
+            FieldVariant::Named(NameOrOptions(options)) => options.name = Some(name),
+            FieldVariant::Flag(NameOrOptions(options)) => options.name = Some(name),
+            FieldVariant::Count(NameOrOptions(options)) => options.name = Some(name),
+            FieldVariant::Unnamed(..) => return false,
+        }
+        true
+    }
+
+    /// Whether the field has any option on how it is rendered as arguments
+    pub fn has_argument_options(&self) -> bool {
+        !matches!(*self.variant, FieldVariant::Unnamed(..))
+            || self.flatten.is_some()
+            || self.with.is_some()
+            || self.format.is_some()
+            || self.raw
+            || self.last
+            || self.subcommand
+    }
+}
+
+/// A version such as `since = "2.28"`, checked when parsing the attribute
+pub struct VersionLit {
+    pub parts: Vec<u64>,
+}
+
+impl FromMeta for VersionLit {
+    fn from_value(value: &Lit) -> darling::Result<Self> {
+        let version = LitStr::from_value(value)?;
+        match version.value().parse::<unclap_core::Version>() {
+            Ok(parsed) => Ok(VersionLit {
+                parts: parsed.parts().to_vec(),
+            }),
+            Err(e) => Err(darling::Error::custom(e).with_span(&version)),
+        }
+    }
+}
+
+/// What to do with a set field that the target version of the program does not support
+#[derive(Default)]
+pub enum Unsupported {
+    /// Report the field through the receiver, failing the rendering
+    #[default]
+    Error,
+    /// Leave out the field
+    Skip,
+}
+
+impl FromMeta for Unsupported {
+    fn from_string(value: &str) -> darling::Result<Self> {
+        match value {
+            "error" => Ok(Unsupported::Error),
+            "skip" => Ok(Unsupported::Skip),
+            _ => Err(unknown_value(value, &["error", "skip"])),
+        }
+    }
+}
+
+/// Names of fields in the same struct or variant, as in `requires = "user"`
+/// or `conflicts_with("quiet", "verbose")`
+#[derive(Default)]
+pub struct FieldNames(pub Vec<LitStr>);
+
+impl FromMeta for FieldNames {
+    fn from_value(value: &Lit) -> darling::Result<Self> {
+        LitStr::from_value(value).map(|name| FieldNames(vec![name]))
+    }
+
+    fn from_list(items: &[NestedMeta]) -> darling::Result<Self> {
+        items
+            .iter()
+            .map(|item| match item {
+                NestedMeta::Lit(lit) => LitStr::from_value(lit),
+                NestedMeta::Meta(meta) => {
+                    Err(darling::Error::custom("Expected a field name as a string").with_span(meta))
+                }
+            })
+            .collect::<darling::Result<_>>()
+            .map(FieldNames)
+    }
+}
+
+/// `range(min = 1, max = 10)`, either bound can be left out
+#[derive(FromMeta)]
+pub struct RangeOptions {
+    #[darling(default)]
+    pub min: Option<RangeBound>,
+    #[darling(default)]
+    pub max: Option<RangeBound>,
+}
+
+/// A bound of a range, given like a [`DefaultValue`] as in `min = 1` or `min = "-1"`
+pub struct RangeBound {
+    pub expr: Expr,
+    /// The bound as written, for error messages
+    pub text: String,
+}
+
+impl FromMeta for RangeBound {
+    fn from_value(value: &Lit) -> darling::Result<Self> {
+        let DefaultValue(expr) = DefaultValue::from_value(value)?;
+        let text = match value {
+            Lit::Str(text) => text.value(),
+            _ => value.to_token_stream().to_string(),
+        };
+        Ok(RangeBound { expr, text })
+    }
+}
+
+/// `default = 8`, or an expression in a string as in `default = "Level::Info"`
+pub struct DefaultValue(pub Expr);
+
+impl FromMeta for DefaultValue {
+    fn from_value(value: &Lit) -> darling::Result<Self> {
+        match value {
+            Lit::Str(expr) => Ok(DefaultValue(expr.parse()?)),
+            _ => Ok(DefaultValue(Expr::Lit(ExprLit {
+                attrs: Vec::new(),
+                lit: value.clone(),
+            }))),
+        }
+    }
+}
+
+/// `format = "{}x{}"` or `format("{}s", as_secs())`
+pub struct FormatOptions {
+    pub template: LitStr,
+    /// The values of the positional placeholders, read from the field
+    pub args: Vec<FormatArg>,
+}
+
+pub enum FormatArg {
+    /// `width` or `0`, reading a member of the field
+    Member(Member),
+    /// `as_secs()`, calling a method without arguments on the field
+    Method(Ident),
+}
+
+impl FromMeta for FormatOptions {
+    fn from_value(value: &Lit) -> darling::Result<Self> {
+        Ok(FormatOptions {
+            template: LitStr::from_value(value)?,
+            args: Vec::new(),
+        })
+    }
+
+    fn from_list(items: &[NestedMeta]) -> darling::Result<Self> {
+        let (template, args) = match items {
+            [NestedMeta::Lit(template), args @ ..] => (template, args),
+            _ => return Err(darling::Error::custom("Expected a format string")),
+        };
+        let args = args
+            .iter()
+            .map(|arg| FormatArg::from_nested_meta(arg).map_err(|e| e.with_span(arg)))
+            .collect::<darling::Result<_>>()?;
+        Ok(FormatOptions {
+            template: LitStr::from_value(template)?,
+            args,
+        })
+    }
+}
+
+impl FromMeta for FormatArg {
+    fn from_nested_meta(item: &NestedMeta) -> darling::Result<Self> {
+        match item {
+            NestedMeta::Lit(Lit::Int(index)) => Ok(FormatArg::Member(Member::Unnamed(Index {
+                index: index.base10_parse()?,
+                span: index.span(),
+            }))),
+            NestedMeta::Meta(Meta::Path(path)) => match path.get_ident() {
+                Some(ident) => Ok(FormatArg::Member(Member::Named(ident.clone()))),
+                None => Err(darling::Error::custom("Expected a field name")),
+            },
+            NestedMeta::Meta(Meta::List(list)) if list.nested.is_empty() => {
+                match list.path.get_ident() {
+                    Some(ident) => Ok(FormatArg::Method(ident.clone())),
+                    None => Err(darling::Error::custom("Expected a method name")),
+                }
+            }
+            _ => Err(darling::Error::custom(
+                "Expected a field name, a tuple index or a method call such as `as_secs()`",
+            )),
+        }
+    }
 }
 
 #[derive(FromMeta)]
-#[darling(default)]
 pub enum EnumVariant {
     #[darling(rename = "named")]
     Named(Override<LitStr>),
     #[darling(rename = "unnamed")]
-    Unnamed(Override<()>),
+    Unnamed(()),
+    #[darling(rename = "subcommand")]
+    Subcommand(()),
 }
 
-impl Default for EnumVariant {
-    fn default() -> Self {
-        EnumVariant::Unnamed(Override::Inherit)
+#[derive(FromVariant)]
+#[darling(attributes(argument), forward_attrs(doc))]
+pub struct UnclapVariant_ {
+    pub ident: syn::Ident,
+    pub attrs: Vec<Attribute>,
+    pub fields: darling::ast::Fields<syn::Field>,
+    /// Defaults to `subcommand` for variants with named fields, `unnamed` otherwise
+    #[darling(default)]
+    pub variant: Option<SpannedValue<EnumVariant>>,
+    #[darling(default)]
+    pub rename: Option<LitStr>,
+}
+
+impl UnclapVariant_ {
+    pub fn variant(&self) -> &EnumVariant {
+        match (&self.variant, self.fields.style) {
+            (Some(variant), _) => variant,
+            (None, darling::ast::Style::Struct) => &EnumVariant::Subcommand(()),
+            (None, _) => &EnumVariant::Unnamed(()),
+        }
     }
 }
+pub type UnclapVariant = SpannedValue<UnclapVariant_>;
 
 #[derive(FromVariant)]
 #[darling(attributes(argument))]
-pub struct UnclapVariant_ {
+pub struct UnclapValueVariant {
     pub ident: syn::Ident,
-    pub fields: darling::ast::Fields<syn::Field>,
+    pub fields: darling::ast::Fields<darling::util::Ignored>,
     #[darling(default)]
-    pub variant: SpannedValue<EnumVariant>,
+    pub rename: Option<LitStr>,
 }
-pub type UnclapVariant = SpannedValue<UnclapVariant_>;
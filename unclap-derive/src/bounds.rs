@@ -0,0 +1,131 @@
+use proc_macro2::TokenStream;
+use quote::quote_spanned;
+use syn::{
+    parse_quote, spanned::Spanned, visit::Visit, GenericParam, Generics, Ident, Lifetime, Path,
+    Type, WherePredicate,
+};
+
+/// Checks whether a type mentions any of the generic parameters of the deriving item.
+struct UsesGenerics<'a> {
+    generics: &'a Generics,
+    found: bool,
+}
+
+impl<'a, 'ast> Visit<'ast> for UsesGenerics<'a> {
+    fn visit_path(&mut self, path: &'ast Path) {
+        if path.leading_colon.is_none() {
+            if let Some(first) = path.segments.first() {
+                let is_param = self.generics.params.iter().any(|param| match param {
+                    GenericParam::Type(ty) => ty.ident == first.ident,
+                    GenericParam::Const(c) => c.ident == first.ident,
+                    GenericParam::Lifetime(_) => false,
+                });
+                self.found |= is_param;
+            }
+        }
+        syn::visit::visit_path(self, path);
+    }
+
+    fn visit_lifetime(&mut self, lifetime: &'ast Lifetime) {
+        self.found |= self
+            .generics
+            .lifetimes()
+            .any(|def| def.lifetime == *lifetime);
+    }
+}
+
+/// Gathers the trait bounds on field types that the generated impl relies on.
+///
+/// Field types that do not depend on the generics of the item are checked by a
+/// local assertion item, which gives an error pointing at the field. Local items
+/// can not refer to the generics of the surrounding impl, so bounds on all other
+/// field types are added to the where-clause of the impl instead.
+pub struct ImplBounds<'a> {
+    generics: &'a Generics,
+    predicates: Vec<WherePredicate>,
+}
+
+impl<'a> ImplBounds<'a> {
+    pub fn new(generics: &'a Generics) -> Self {
+        ImplBounds {
+            generics,
+            predicates: Vec::new(),
+        }
+    }
+
+    fn uses_generics(&self, ty: &Type) -> bool {
+        let mut visitor = UsesGenerics {
+            generics: self.generics,
+            found: false,
+        };
+        visitor.visit_type(ty);
+        visitor.found
+    }
+
+    /// Require that `ty: bound` holds, returning an assertion item if it can be checked locally.
+    pub fn require(&mut self, ty: &Type, bound: TokenStream, assert_name: &Ident) -> TokenStream {
+        self.require_predicates(ty, vec![parse_quote!(#ty: #bound)], assert_name)
+    }
+
+    /// Require that `ty` is a collection, where each item borrowed from it satisfies `bound`.
+    pub fn require_items(
+        &mut self,
+        ty: &Type,
+        bound: TokenStream,
+        assert_name: &Ident,
+    ) -> TokenStream {
+        let predicates = vec![
+            parse_quote!(for<'__unclap> &'__unclap #ty: ::std::iter::IntoIterator),
+            parse_quote!(for<'__unclap> <&'__unclap #ty as ::std::iter::IntoIterator>::Item: #bound),
+        ];
+        self.require_predicates(ty, predicates, assert_name)
+    }
+
+    /// Require that `ty` is a collection of key-value pairs, whose keys and values satisfy `bound`.
+    pub fn require_key_values(
+        &mut self,
+        ty: &Type,
+        pairs: TokenStream,
+        bound: TokenStream,
+        assert_name: &Ident,
+    ) -> TokenStream {
+        let predicates = vec![
+            parse_quote!(#ty: #pairs),
+            parse_quote!(<#ty as #pairs>::Key: #bound),
+            parse_quote!(<#ty as #pairs>::Value: #bound),
+        ];
+        self.require_predicates(ty, predicates, assert_name)
+    }
+
+    /// Require an arbitrary predicate on the impl, such as `Self: Default`.
+    pub fn require_impl(&mut self, predicate: WherePredicate) {
+        self.predicates.push(predicate);
+    }
+
+    fn require_predicates(
+        &mut self,
+        ty: &Type,
+        predicates: Vec<WherePredicate>,
+        assert_name: &Ident,
+    ) -> TokenStream {
+        if self.uses_generics(ty) {
+            self.predicates.extend(predicates);
+            TokenStream::new()
+        } else {
+            quote_spanned! {ty.span()=>
+                #[allow(dead_code)]
+                struct #assert_name where #( #predicates ),*;
+            }
+        }
+    }
+
+    /// The generics of the item, extended by all bounds that could not be checked locally.
+    pub fn into_generics(self) -> Generics {
+        let mut generics = self.generics.clone();
+        generics
+            .make_where_clause()
+            .predicates
+            .extend(self.predicates);
+        generics
+    }
+}
@@ -0,0 +1,93 @@
+use crate::attrs::{FormatArg, FormatOptions};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Ident, Index, Member};
+
+/// The placeholders of a format string
+#[derive(Default)]
+struct Placeholders {
+    /// The number of positional arguments referenced
+    positional: usize,
+    /// The names referenced, in order of first appearance
+    named: Vec<String>,
+}
+
+impl Placeholders {
+    /// Scans `template` leniently, leaving the reporting of malformed templates to `format!`
+    fn scan(template: &str) -> Self {
+        let mut placeholders = Placeholders::default();
+        let mut implicit = 0;
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            rest = &rest[start + 1..];
+            if let Some(escaped) = rest.strip_prefix('{') {
+                rest = escaped;
+                continue;
+            }
+            let end = rest.find('}').unwrap_or(rest.len());
+            let argument = rest[..end].split(':').next().unwrap_or("").trim();
+            rest = &rest[end..];
+            if argument.is_empty() {
+                implicit += 1;
+                placeholders.positional = placeholders.positional.max(implicit);
+            } else if let Ok(index) = argument.parse::<usize>() {
+                placeholders.positional = placeholders.positional.max(index + 1);
+            } else if !placeholders.named.iter().any(|name| name == argument) {
+                placeholders.named.push(argument.to_owned());
+            }
+        }
+        placeholders
+    }
+}
+
+/// The `format!` expression for a field formatted with `options`. `value_ref`
+/// evaluates to a reference to the field.
+///
+/// Without explicit arguments, a single positional placeholder stands for the
+/// field itself and several stand for the elements of a tuple. Named
+/// placeholders always read the member of the same name.
+pub fn format_value(options: &FormatOptions, value_ref: &TokenStream) -> FormattedValue {
+    let template = &options.template;
+    let placeholders = Placeholders::scan(&template.value());
+    let mut uses_value = false;
+    let positional: Vec<TokenStream> = if !options.args.is_empty() {
+        options
+            .args
+            .iter()
+            .map(|arg| match arg {
+                FormatArg::Member(member) => quote! { &(#value_ref).#member },
+                FormatArg::Method(method) => quote! { (#value_ref).#method() },
+            })
+            .collect()
+    } else if placeholders.positional == 1 {
+        uses_value = true;
+        vec![value_ref.clone()]
+    } else {
+        (0..placeholders.positional)
+            .map(|index| {
+                let member = Member::Unnamed(Index {
+                    index: index as u32,
+                    span: template.span(),
+                });
+                quote! { &(#value_ref).#member }
+            })
+            .collect()
+    };
+    // Invalid names are left out, for `format!` to report them
+    let named = placeholders.named.iter().filter_map(|name| {
+        let mut ident = syn::parse_str::<Ident>(name).ok()?;
+        ident.set_span(template.span());
+        Some(quote! { #ident = &(#value_ref).#ident })
+    });
+    FormattedValue {
+        expr: quote! { ::std::format!(#template, #( #positional, )* #( #named ),*) },
+        uses_value,
+    }
+}
+
+pub struct FormattedValue {
+    /// An expression evaluating to the formatted `String`
+    pub expr: TokenStream,
+    /// Whether the field itself is formatted, requiring it to implement `Display`
+    pub uses_value: bool,
+}
@@ -1,18 +1,30 @@
 //! Procedural macros for defining `Argument` and `Program`
 extern crate proc_macro;
-use attrs::{EnumVariant, FieldVariant, UnclapField, UnclapVariant};
+use attrs::{
+    doc_comment, DefaultValue, Delimiter, EnumVariant, FieldVariant, Multiple, NameOrOptions,
+    NamedOptions, UnclapContainer, UnclapField, UnclapVariant, Unsupported, ValueStyle, VersionLit,
+};
+use bounds::ImplBounds;
 use convert_case::{Case, Casing};
-use darling::{util::Override, FromField, FromVariant};
+use darling::{util::Override, FromDeriveInput, FromField, FromVariant};
 use proc_macro::TokenStream as TS1;
 use proc_macro2::{Span, TokenStream};
 use proc_macro_error::abort_call_site;
-use quote::{format_ident, quote, quote_spanned};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use std::mem::replace;
 use syn::{
-    ext::IdentExt, parse_macro_input, punctuated::Punctuated, spanned::Spanned, token::Comma,
-    Attribute, Data, DataStruct, DeriveInput, Field, Fields, Ident, LitStr, Member, Variant,
+    ext::IdentExt, parse_macro_input, parse_quote, punctuated::Punctuated, spanned::Spanned,
+    token::Comma, Data, DataStruct, DeriveInput, Field, Fields, FieldsNamed, FieldsUnnamed,
+    Generics, Ident, LitStr, Member, Type, Variant,
 };
 
 mod attrs;
+mod bounds;
+mod format;
+mod schema;
+mod types;
+mod validate;
+mod value_enum;
 
 #[proc_macro_derive(Argument, attributes(argument))]
 pub fn derive_argument(item: TS1) -> TS1 {
@@ -24,261 +36,1347 @@ pub fn derive_argument(item: TS1) -> TS1 {
     res.into()
 }
 
+#[proc_macro_derive(ArgumentValue, attributes(argument))]
+pub fn derive_argument_value(item: TS1) -> TS1 {
+    let input: DeriveInput = parse_macro_input!(item);
+    value_enum::derive_argument_value(&input).into()
+}
+
 fn do_derive_argument(input: &DeriveInput) -> TokenStream {
     let ident = &input.ident;
+    let container = match UnclapContainer::from_derive_input(input) {
+        Ok(container) => Container::new(&container),
+        Err(e) => return e.write_errors(),
+    };
 
+    let generics = &input.generics;
     match input.data {
         Data::Struct(DataStruct {
-            fields: Fields::Named(ref fields),
+            fields: Fields::Named(FieldsNamed {
+                named: ref fields, ..
+            }),
             ..
-        }) => unclap_for_struct(ident, &fields.named, &input.attrs),
-        Data::Struct(DataStruct {
-            fields: Fields::Unnamed(ref fields),
+        })
+        | Data::Struct(DataStruct {
+            fields:
+                Fields::Unnamed(FieldsUnnamed {
+                    unnamed: ref fields,
+                    ..
+                }),
             ..
-        }) => unclap_for_struct(ident, &fields.unnamed, &input.attrs),
-        Data::Enum(ref e) => unclap_for_enum(ident, &e.variants, &input.attrs),
+        }) => {
+            let argument = unclap_for_struct(ident, generics, fields, &container);
+            let schema = schema::schema_for_struct(ident, generics, fields, &container);
+            let validate = validate::validate_for_struct(ident, generics, fields, &container);
+            quote! {
+                #argument
+                #schema
+                #validate
+            }
+        }
+        Data::Enum(ref e) => {
+            let argument = unclap_for_enum(ident, generics, &e.variants, &container);
+            let schema = schema::schema_for_enum(ident, generics, &e.variants, &container);
+            let validate = validate::validate_for_enum(ident, generics, &e.variants, &container);
+            quote! {
+                #argument
+                #schema
+                #validate
+            }
+        }
         _ => abort_call_site!("`#[derive(Argument)]` only supports non-unit structs and enums"),
     }
 }
 
-fn flag_name_from_ident(ident: &Ident) -> LitStr {
-    let flag_name = String::from("--") + &ident.unraw().to_string().to_case(Case::Kebab);
-    LitStr::new(&flag_name, Span::call_site())
+/// Settings of the deriving item that apply to all of its fields and variants
+struct Container {
+    /// The case convention of derived flag names, `kebab-case` by default
+    case: Case,
+    /// The leading dashes of derived flag names, `--` by default
+    prefix: String,
+    /// The default style of named fields, `separate` by default
+    style: ValueStyle,
+    /// The parameter holding the prefix of a flattened argument in the generated code
+    flag_prefix_name: Ident,
+    /// Holds `Default::default()` in the generated code, if fields equal to it are left out
+    defaults_name: Option<Ident>,
+    /// Sort options before positional fields
+    options_first: bool,
+    /// The path of the `unclap` crate, re-exporting the core traits
+    krate: TokenStream,
+    /// The path of the support module of the `unclap` crate
+    support: TokenStream,
+    /// The type of single arguments
+    arg_type: TokenStream,
+    /// The doc comment of the deriving item
+    doc: Option<String>,
+}
+
+impl Container {
+    fn new(attrs: &UnclapContainer) -> Self {
+        let krate = match &attrs.krate {
+            Some(path) => path.to_token_stream(),
+            None => quote! { ::unclap },
+        };
+        Container {
+            case: attrs.rename_all.map_or(Case::Kebab, |rule| rule.0),
+            prefix: attrs
+                .prefix
+                .as_ref()
+                .map_or_else(|| String::from("--"), LitStr::value),
+            style: attrs.style.unwrap_or(ValueStyle::Separate),
+            flag_prefix_name: Ident::new("flag_prefix", Span::mixed_site()),
+            defaults_name: if attrs.omit_defaults {
+                Some(Ident::new("__unclap_defaults", Span::mixed_site()))
+            } else {
+                None
+            },
+            options_first: attrs.options_first,
+            krate: krate.clone(),
+            support: quote! { #krate::support },
+            arg_type: match &attrs.arg_type {
+                Some(arg_type) => arg_type.to_token_stream(),
+                None => quote! { ::std::ffi::OsString },
+            },
+            doc: doc_comment(&attrs.attrs),
+        }
+    }
+
+    fn name_from_ident(&self, ident: &Ident) -> String {
+        ident.unraw().to_string().to_case(self.case)
+    }
+
+    fn flag_name_from_ident(&self, ident: &Ident) -> FlagName {
+        FlagName::Derived {
+            dashes: self.prefix.clone(),
+            negation: String::new(),
+            stem: self.name_from_ident(ident),
+        }
+    }
+
+    fn flag_name_for_field(&self, field: &UnclapField, user: Option<&LitStr>) -> Option<FlagName> {
+        match user {
+            Some(name) => Some(FlagName::Explicit(name.clone())),
+            None => field
+                .ident
+                .as_ref()
+                .map(|ident| self.flag_name_from_ident(ident)),
+        }
+    }
+
+    /// The name of a named field, which has no leading dashes for operands
+    fn named_flag_name(&self, field: &UnclapField, options: &NamedOptions) -> Option<FlagName> {
+        let flag_name = self.flag_name_for_field(field, options.name.as_ref())?;
+        Some(match (flag_name, self.named_style(options)) {
+            (FlagName::Derived { negation, stem, .. }, ValueStyle::Operand) => FlagName::Derived {
+                dashes: String::new(),
+                negation,
+                stem,
+            },
+            (flag_name, _) => flag_name,
+        })
+    }
+
+    fn named_style(&self, options: &NamedOptions) -> ValueStyle {
+        options.style.unwrap_or(self.style)
+    }
+
+    /// The `Argument` trait for the argument type
+    fn argument_trait(&self) -> TokenStream {
+        let krate = &self.krate;
+        let arg_type = &self.arg_type;
+        quote! { #krate::Argument<#arg_type> }
+    }
+
+    /// The `SingleArgument` trait for the argument type
+    fn single_argument_trait(&self) -> TokenStream {
+        let support = &self.support;
+        let arg_type = &self.arg_type;
+        quote! { #support::SingleArgument<#arg_type> }
+    }
+
+    /// The `FlattenArgument` trait for the argument type
+    fn flatten_trait(&self) -> TokenStream {
+        let support = &self.support;
+        let arg_type = &self.arg_type;
+        quote! { #support::FlattenArgument<#arg_type> }
+    }
+
+    /// Appends `arg`, an expression evaluating to an `Argument`, to the receiver
+    fn append_call(&self, arg: TokenStream, receiver_name: &Ident) -> TokenStream {
+        let krate = &self.krate;
+        let arg_type = &self.arg_type;
+        quote! {
+            #krate::Argument::<#arg_type>::append_to(&#arg, #receiver_name);
+        }
+    }
+
+    /// The builder call configuring the style of a `Named` argument
+    fn named_style_call(&self, options: &NamedOptions) -> Option<TokenStream> {
+        let support = &self.support;
+        match self.named_style(options) {
+            ValueStyle::Separate => None,
+            ValueStyle::Equals | ValueStyle::Operand => {
+                Some(quote! { .with_style(#support::NamedStyle::Equals) })
+            }
+            ValueStyle::Attached => Some(quote! { .with_style(#support::NamedStyle::Attached) }),
+        }
+    }
+
+    /// The negative spelling of a negatable flag, `--no-<name>` unless given explicitly.
+    /// The prefix of flattened arguments goes after the `no-`, as in `--no-<prefix><name>`.
+    fn negative_flag_name(
+        &self,
+        field: &UnclapField,
+        positive: &FlagName,
+        user: Option<&LitStr>,
+    ) -> FlagName {
+        match (user, positive, &field.ident) {
+            (Some(name), _, _) => FlagName::Explicit(name.clone()),
+            (None, FlagName::Derived { dashes, stem, .. }, Some(ident)) => {
+                let negative = format!("no_{}", ident.unraw()).to_case(self.case);
+                // Cases such as camelCase change the first letter of the stem
+                match negative.strip_suffix(stem.as_str()) {
+                    Some(negation) => FlagName::Derived {
+                        dashes: dashes.clone(),
+                        negation: negation.to_owned(),
+                        stem: stem.clone(),
+                    },
+                    None => FlagName::Derived {
+                        dashes: dashes.clone(),
+                        negation: String::new(),
+                        stem: negative,
+                    },
+                }
+            }
+            (None, FlagName::Derived { dashes, stem, .. }, None) => FlagName::Derived {
+                dashes: dashes.clone(),
+                negation: String::from("no-"),
+                stem: stem.clone(),
+            },
+            (None, FlagName::Explicit(name), _) => {
+                let name_value = name.value();
+                let dashes_len = name_value
+                    .find(char::is_alphanumeric)
+                    .unwrap_or(name_value.len());
+                let (dashes, stem) = name_value.split_at(dashes_len);
+                let negative = format!("{}no-{}", dashes, stem);
+                FlagName::Explicit(LitStr::new(&negative, name.span()))
+            }
+        }
+    }
+
+    fn flag_name_for_variant(&self, variant: &UnclapVariant, user: &Override<LitStr>) -> FlagName {
+        match (user, &variant.rename) {
+            (Override::Explicit(name), _) => FlagName::Explicit(name.clone()),
+            (Override::Inherit, Some(rename)) => FlagName::Derived {
+                dashes: self.prefix.clone(),
+                negation: String::new(),
+                stem: rename.value(),
+            },
+            (Override::Inherit, None) => self.flag_name_from_ident(&variant.ident),
+        }
+    }
+
+    fn subcommand_name_for_variant(&self, variant: &UnclapVariant) -> LitStr {
+        variant.rename.clone().unwrap_or_else(|| {
+            LitStr::new(&self.name_from_ident(&variant.ident), variant.ident.span())
+        })
+    }
+}
+
+/// A flag name, either given by the user or derived from an identifier
+enum FlagName {
+    Explicit(LitStr),
+    /// Derived names are namespaced by the prefix of flattened arguments,
+    /// which is inserted between the leading dashes and the stem, after the
+    /// `no-` of negative flags.
+    Derived {
+        dashes: String,
+        negation: String,
+        stem: String,
+    },
+}
+
+impl FlagName {
+    fn to_expr(&self, container: &Container) -> TokenStream {
+        match self {
+            FlagName::Explicit(name) => quote! { #name },
+            FlagName::Derived {
+                dashes,
+                negation,
+                stem,
+            } => {
+                let flag_prefix = &container.flag_prefix_name;
+                quote! { ::std::format!("{}{}{}{}", #dashes, #negation, #flag_prefix, #stem) }
+            }
+        }
+    }
+}
+
+impl FlagName {
+    /// The name as passed when not flattened into another argument
+    fn unprefixed(&self) -> String {
+        match self {
+            FlagName::Explicit(name) => name.value(),
+            FlagName::Derived {
+                dashes,
+                negation,
+                stem,
+            } => format!("{}{}{}", dashes, negation, stem),
+        }
+    }
 }
 
-fn flag_name_for_field(field: &UnclapField, user: &Override<LitStr>) -> Option<LitStr> {
-    user.clone()
-        .explicit()
-        .or_else(|| field.ident.as_ref().map(|id| flag_name_from_ident(id)))
+/// The flag names used by the fields of a struct or variant, to detect duplicates.
+/// Flattened arguments are not known to the derive, so their names are not checked.
+#[derive(Default)]
+struct FlagNames {
+    /// Each name together with the item using it
+    used: Vec<(String, String)>,
 }
 
-fn flag_name_for_variant(variant: &UnclapVariant, user: &Override<LitStr>) -> LitStr {
-    user.clone()
-        .explicit()
-        .unwrap_or_else(|| flag_name_from_ident(&variant.ident))
+impl FlagNames {
+    /// Uses `name` for `user`, returning an error at `span` if it is already in use
+    fn claim(&mut self, name: &FlagName, span: Span, user: &dyn ToString) -> Option<TokenStream> {
+        let name = name.unprefixed();
+        if let Some((_, previous)) = self.used.iter().find(|(used, _)| *used == name) {
+            let message = format!(
+                "Duplicate flag name `{}`, also used by `{}`",
+                name, previous
+            );
+            return Some(quote_spanned! { span=>
+                compile_error!(#message);
+            });
+        }
+        self.used.push((name, user.to_string()));
+        None
+    }
+
+    /// Uses all names a field passes, returning the errors for duplicates
+    fn claim_field(
+        &mut self,
+        field: &UnclapField,
+        field_name: &Member,
+        container: &Container,
+    ) -> TokenStream {
+        if field.skip || field.flatten.is_some() {
+            return TokenStream::new();
+        }
+        let mut names = Vec::new();
+        match &*field.variant {
+            FieldVariant::Unnamed(..) => {}
+            FieldVariant::Named(NameOrOptions(options)) => {
+                names.extend(container.named_flag_name(field, options));
+            }
+            FieldVariant::Flag(NameOrOptions(options)) => {
+                if let Some(positive) = container.flag_name_for_field(field, options.name.as_ref())
+                {
+                    if options.negatable {
+                        names.push(container.negative_flag_name(
+                            field,
+                            &positive,
+                            options.negative.as_ref(),
+                        ));
+                    }
+                    names.push(positive);
+                }
+            }
+            FieldVariant::Count(NameOrOptions(options)) => {
+                names.extend(container.flag_name_for_field(field, options.name.as_ref()));
+                names.extend(options.negative.clone().map(FlagName::Explicit));
+            }
+        }
+        let user = quote! { #field_name };
+        names
+            .iter()
+            .filter_map(|name| {
+                let span = match (name, &field.ident) {
+                    (FlagName::Explicit(name), _) => name.span(),
+                    (FlagName::Derived { .. }, Some(ident)) => ident.span(),
+                    (FlagName::Derived { .. }, None) => field.variant.span(),
+                };
+                self.claim(name, span, &user)
+            })
+            .collect()
+    }
 }
 
-fn make_assert_impl_name(_field: &UnclapField, field_name: &Member) -> Ident {
-    let mut ident = format_ident!("__unclap_assert{:}", field_name);
+/// A hygienic identifier for generated items of a field, also for raw field identifiers
+fn make_field_ident(prefix: &str, field_name: &Member) -> Ident {
+    let mut ident = match field_name {
+        Member::Named(name) => format_ident!("{}{}", prefix, name.unraw()),
+        Member::Unnamed(index) => format_ident!("{}{}", prefix, index),
+    };
     ident.set_span(Span::mixed_site());
     ident
 }
 
+pub(crate) fn make_assert_impl_name(_field: &UnclapField, field_name: &Member) -> Ident {
+    make_field_ident("__unclap_assert", field_name)
+}
+
+fn make_value_name() -> Ident {
+    Ident::new("__unclap_value", Span::mixed_site())
+}
+
+fn make_binding_name(field_name: &Member) -> Ident {
+    make_field_ident("__unclap_field_", field_name)
+}
+
+/// The error for a field variant that needs a flag name on a field without an identifier
+fn missing_flag_name(field: &UnclapField, variant: &str) -> TokenStream {
+    let message = format!(
+        "Can not use #[argument(variant({}))] on an unnamed field without providing a name",
+        variant
+    );
+    quote_spanned! { field.variant.span()=>
+        compile_error!(#message);
+    }
+}
+
+/// The type and a reference to the value of a field, which is the content of an optional field.
+fn optional_value<'a>(field_ty: &'a Type, field_ref: &TokenStream) -> (&'a Type, TokenStream) {
+    match types::option_inner(field_ty) {
+        Some(inner_ty) => (inner_ty, make_value_name().into_token_stream()),
+        None => (field_ty, field_ref.clone()),
+    }
+}
+
+/// Guards `append` for optional fields, as produced by [`optional_value`].
+/// An absent optional field is left out entirely, including its name.
+fn if_present(field_ty: &Type, field_ref: &TokenStream, append: TokenStream) -> TokenStream {
+    if types::option_inner(field_ty).is_some() {
+        let value = make_value_name();
+        quote! {
+            if let ::std::option::Option::Some(#value) = #field_ref {
+                #append
+            }
+        }
+    } else {
+        append
+    }
+}
+
+/// Appends a collection of strings verbatim, as untrusted arguments
+fn raw_field(
+    field: &UnclapField,
+    field_name: &Member,
+    field_ref: &TokenStream,
+    receiver_name: &Ident,
+    container: &Container,
+    bounds: &mut ImplBounds,
+) -> TokenStream {
+    if !matches!(*field.variant, FieldVariant::Unnamed(..))
+        || field.with.is_some()
+        || field.format.is_some()
+    {
+        return quote_spanned! { field.variant.span()=>
+            compile_error!("A `raw` field can not be named or rendered with `with` or `format`");
+        };
+    }
+    let field_ty = &field.ty;
+    let (value_ty, value_ref) = optional_value(field_ty, field_ref);
+    let assert_name = make_assert_impl_name(field, field_name);
+    let assert_strings = bounds.require_items(value_ty, container.argument_trait(), &assert_name);
+    let support = &container.support;
+    let append = if_present(
+        field_ty,
+        field_ref,
+        container.append_call(quote! { #support::Raw::new(#value_ref) }, receiver_name),
+    );
+    quote! {
+        #assert_strings
+        #append
+    }
+}
+
+/// Sets the environment variable of an `env` field. An absent optional field has no
+/// arguments, which leaves the variable untouched unless it is removed with `remove_empty`.
+fn env_field(
+    field: &UnclapField,
+    field_name: &Member,
+    field_ref: &TokenStream,
+    receiver_name: &Ident,
+    container: &Container,
+    bounds: &mut ImplBounds,
+) -> TokenStream {
+    if field.has_argument_options() || field.stdin {
+        return quote_spanned! { field.variant.span()=>
+            compile_error!("An `env` field is passed in the environment and can not be rendered as arguments");
+        };
+    }
+    let NameOrOptions(options) = field.env.as_ref().expect("env_field requires `env`");
+    let env = match &options.name {
+        Some(env) => env,
+        None => {
+            return quote_spanned! { field.variant.span()=>
+                compile_error!("An `env` field needs the name of the variable, as in `env = \"NAME\"`");
+            };
+        }
+    };
+    let remove_empty = options.remove_empty;
+    let krate = &container.krate;
+    let support = &container.support;
+    let field_ty = &field.ty;
+    let assert_name = make_assert_impl_name(field, field_name);
+    let assert_arg = bounds.require(
+        field_ty,
+        quote! { #krate::Argument<::std::ffi::OsString> },
+        &assert_name,
+    );
+    let append = container.append_call(
+        quote! { #support::EnvVar::new(#env, #field_ref).removing_empty(#remove_empty) },
+        receiver_name,
+    );
+    quote! {
+        #assert_arg
+        #append
+    }
+}
+
+/// Feeds a `stdin` field to the program. An absent optional field feeds nothing.
+fn stdin_field(
+    field: &UnclapField,
+    field_name: &Member,
+    field_ref: &TokenStream,
+    receiver_name: &Ident,
+    container: &Container,
+    bounds: &mut ImplBounds,
+) -> TokenStream {
+    if field.has_argument_options() {
+        return quote_spanned! { field.variant.span()=>
+            compile_error!("A `stdin` field is fed to the program and can not be rendered as arguments");
+        };
+    }
+    let support = &container.support;
+    let field_ty = &field.ty;
+    let (value_ty, value_ref) = optional_value(field_ty, field_ref);
+    let assert_name = make_assert_impl_name(field, field_name);
+    let assert_payload =
+        bounds.require(value_ty, quote! { #support::IsStdinPayload }, &assert_name);
+    let append = if_present(
+        field_ty,
+        field_ref,
+        container.append_call(quote! { #support::StdinArg(#value_ref) }, receiver_name),
+    );
+    quote! {
+        #assert_payload
+        #append
+    }
+}
+
+/// Appends a field rendered by `with` or `format` instead of its `Argument` implementation.
+fn custom_field(
+    field: &UnclapField,
+    field_name: &Member,
+    field_ref: &TokenStream,
+    receiver_name: &Ident,
+    container: &Container,
+    bounds: &mut ImplBounds,
+) -> TokenStream {
+    let support = &container.support;
+    let argument = container.argument_trait();
+    let field_ty = &field.ty;
+    let (value_ty, value_ref) = optional_value(field_ty, field_ref);
+    let (assert_display, formatted) = match (&field.with, &field.format) {
+        (Some(with), Some(_)) => {
+            return quote_spanned! { with.span()=>
+                compile_error!("Can not use both `with` and `format` on the same field");
+            };
+        }
+        (Some(_), None) => (TokenStream::new(), None),
+        (None, Some(format)) => {
+            let formatted = format::format_value(format, &value_ref);
+            let assert_display = if formatted.uses_value {
+                let assert_name = make_assert_impl_name(field, field_name);
+                bounds.require(value_ty, quote! { ::std::fmt::Display }, &assert_name)
+            } else {
+                TokenStream::new()
+            };
+            (assert_display, Some(formatted.expr))
+        }
+        (None, None) => unreachable!("custom_field requires `with` or `format`"),
+    };
+    let append = match (&*field.variant, formatted) {
+        (FieldVariant::Unnamed(..), Some(formatted)) => quote! {
+            <::std::string::String as #argument>::append_to(&#formatted, #receiver_name);
+        },
+        (FieldVariant::Unnamed(..), None) => {
+            let with = &field.with;
+            quote! {
+                #with(#value_ref, #receiver_name);
+            }
+        }
+        (FieldVariant::Named(NameOrOptions(options)), formatted) => {
+            if options.key_value.is_some() || !matches!(options.multiple, Multiple::Once) {
+                return quote_spanned! { field.variant.span()=>
+                    compile_error!("Can not combine `with` or `format` with `multiple` or `key_value`");
+                };
+            }
+            let flag_name = match container.named_flag_name(field, options) {
+                Some(flag_name) => flag_name.to_expr(container),
+                None => return missing_flag_name(field, "named"),
+            };
+            let style = container.named_style_call(options);
+            match formatted {
+                Some(formatted) => container.append_call(
+                    quote! { #support::Named::new(#flag_name, &#formatted) #style },
+                    receiver_name,
+                ),
+                // A function passing no arguments leaves out the name as well
+                None => {
+                    let with = &field.with;
+                    let collected = Ident::new("__unclap_collected", Span::mixed_site());
+                    let append = container.append_call(
+                        quote! {
+                            #support::Named::new(#flag_name, &#support::Verbatim(&#collected)) #style
+                        },
+                        receiver_name,
+                    );
+                    let arg_type = &container.arg_type;
+                    quote! {
+                        let mut #collected: ::std::vec::Vec<#arg_type> = ::std::vec::Vec::new();
+                        #with(#value_ref, &mut #collected);
+                        if !#collected.is_empty() {
+                            #append
+                        }
+                    }
+                }
+            }
+        }
+        (FieldVariant::Flag(..), _) | (FieldVariant::Count(..), _) => {
+            return quote_spanned! { field.variant.span()=>
+                compile_error!("`with` and `format` only apply to unnamed and named fields");
+            };
+        }
+    };
+    let append = if_present(field_ty, field_ref, append);
+    quote! {
+        #assert_display
+        #append
+    }
+}
+
+/// Appends a single field. `field_ref` is an expression evaluating to a reference to the field.
 fn wrapped_field(
     field: &UnclapField,
     field_name: &Member,
-    self_name: &Ident,
+    field_ref: &TokenStream,
     receiver_name: &Ident,
+    container: &Container,
+    bounds: &mut ImplBounds,
 ) -> TokenStream {
+    let support = &container.support;
+    let argument = container.argument_trait();
     let field_ty = &field.ty;
+    let assert_name = make_assert_impl_name(field, field_name);
+    if field.env.is_some() {
+        return env_field(
+            field,
+            field_name,
+            field_ref,
+            receiver_name,
+            container,
+            bounds,
+        );
+    }
+    if field.stdin {
+        return stdin_field(
+            field,
+            field_name,
+            field_ref,
+            receiver_name,
+            container,
+            bounds,
+        );
+    }
+    if let Some(flatten_options) = &field.flatten {
+        let flatten = container.flatten_trait();
+        let assert_arg = bounds.require(field_ty, flatten.clone(), &assert_name);
+        let flag_prefix = &container.flag_prefix_name;
+        let flag_prefix = match flatten_options
+            .as_ref()
+            .explicit()
+            .and_then(|f| f.prefix.as_ref())
+        {
+            Some(prefix) => quote! { &::std::format!("{}{}", #flag_prefix, #prefix) },
+            None => quote! { #flag_prefix },
+        };
+        return quote! {
+            #assert_arg
+            <#field_ty as #flatten>::append_prefixed(#field_ref, #flag_prefix, #receiver_name);
+        };
+    }
+    if field.subcommand
+        && (!matches!(*field.variant, FieldVariant::Unnamed(..))
+            || field.raw
+            || field.with.is_some()
+            || field.format.is_some())
+    {
+        return quote_spanned! { field.variant.span()=>
+            compile_error!("A `subcommand` field is passed with its own `Argument` implementation");
+        };
+    }
+    if field.raw {
+        return raw_field(
+            field,
+            field_name,
+            field_ref,
+            receiver_name,
+            container,
+            bounds,
+        );
+    }
+    if field.with.is_some() || field.format.is_some() {
+        return custom_field(
+            field,
+            field_name,
+            field_ref,
+            receiver_name,
+            container,
+            bounds,
+        );
+    }
     match &*field.variant {
         FieldVariant::Unnamed(..) => {
-            let assert_name = make_assert_impl_name(field, field_name);
-            let assert_arg = quote_spanned! {field_ty.span()=>
-                #[allow(dead_code)]
-                struct #assert_name where #field_ty: ::unclap_core::Argument;
+            let assert_arg = bounds.require(field_ty, argument.clone(), &assert_name);
+            quote! {
+                #assert_arg
+                <#field_ty as #argument>::append_to(#field_ref, #receiver_name);
+            }
+        }
+        FieldVariant::Named(NameOrOptions(options)) => {
+            let flag_name = match container.named_flag_name(field, options) {
+                Some(flag_name) => flag_name.to_expr(container),
+                None => return missing_flag_name(field, "named"),
             };
+            let style = container.named_style_call(options);
+            let (value_ty, value_ref) = optional_value(field_ty, field_ref);
+            let (assert_arg, value_ref) = match (&options.key_value, &options.multiple) {
+                // Maps are passed as a collection of `key=value` arguments
+                (Some(separator), _) => {
+                    let assert_arg = bounds.require_key_values(
+                        value_ty,
+                        quote! { #support::KeyValuePairs },
+                        container.single_argument_trait(),
+                        &assert_name,
+                    );
+                    let value_ref = quote! {
+                        &#support::KeyValuePairs::key_values(#value_ref, #separator)
+                    };
+                    (assert_arg, value_ref)
+                }
+                (None, Multiple::Once) => {
+                    let assert_arg = bounds.require(value_ty, argument, &assert_name);
+                    (assert_arg, value_ref)
+                }
+                (None, _) => {
+                    let assert_arg = bounds.require_items(value_ty, argument, &assert_name);
+                    (assert_arg, value_ref)
+                }
+            };
+            let append = match &options.multiple {
+                Multiple::Once => container.append_call(
+                    quote! { #support::Named::new(#flag_name, #value_ref) #style },
+                    receiver_name,
+                ),
+                Multiple::Repeat => {
+                    let item = Ident::new("__unclap_item", Span::mixed_site());
+                    let append = container.append_call(
+                        quote! { #support::Named::new(#flag_name, &#item) #style },
+                        receiver_name,
+                    );
+                    quote! {
+                        for #item in #value_ref {
+                            #append
+                        }
+                    }
+                }
+                Multiple::Delimited(Delimiter(delimiter)) => {
+                    let joined = Ident::new("__unclap_joined", Span::mixed_site());
+                    let append = container.append_call(
+                        quote! {
+                            #support::Named::new(
+                                #flag_name,
+                                &#support::Verbatim(::std::slice::from_ref(&#joined)),
+                            )
+                            #style
+                        },
+                        receiver_name,
+                    );
+                    let arg_type = &container.arg_type;
+                    quote! {
+                        if let ::std::option::Option::Some(#joined) =
+                            #support::Delimited::new(#value_ref, #delimiter).joined::<#arg_type>()
+                        {
+                            #append
+                        }
+                    }
+                }
+            };
+            let append = if_present(field_ty, field_ref, append);
             quote! {
                 #assert_arg
-                <#field_ty as ::unclap_core::Argument>::append_to(&#self_name.#field_name, #receiver_name);
+                #append
             }
         }
-        FieldVariant::Named(name) => match flag_name_for_field(field, name) {
-            Some(flag_name) => {
-                let assert_name = make_assert_impl_name(field, field_name);
-                let assert_arg = quote_spanned! {field_ty.span()=>
-                    #[allow(dead_code)]
-                    struct #assert_name where #field_ty: ::unclap_core::Argument;
-                };
+        FieldVariant::Flag(NameOrOptions(options)) => {
+            let flag_name = match container.flag_name_for_field(field, options.name.as_ref()) {
+                Some(flag_name) => flag_name,
+                None => return missing_flag_name(field, "flag"),
+            };
+            if options.negatable {
+                let assert_arg =
+                    bounds.require(field_ty, quote! { #support::IsNegatableFlag }, &assert_name);
+                let negative_name = container
+                    .negative_flag_name(field, &flag_name, options.negative.as_ref())
+                    .to_expr(container);
+                let flag_name = flag_name.to_expr(container);
+                let append = container.append_call(
+                    quote! { #support::NegatableFlagArg::new(#flag_name, #negative_name, #field_ref) },
+                    receiver_name,
+                );
                 quote! {
                     #assert_arg
-                    ::unclap_support::Named::new(&#flag_name, &#self_name.#field_name)
-                        .append_to(#receiver_name);
+                    #append
                 }
-            }
-            None => quote_spanned! { field.variant.span()=>
-                compile_error!("Can not use #[argument(variant(named))] on an unnamed field without providing a name");
-            },
-        },
-        FieldVariant::Flag(name) => match flag_name_for_field(field, name) {
-            Some(flag_name) => {
-                let assert_name = make_assert_impl_name(field, field_name);
-                let assert_arg = quote_spanned! {field_ty.span()=>
-                    #[allow(dead_code)]
-                    struct #assert_name where #field_ty: ::unclap_support::IsArgumentFlag;
-                };
+            } else {
+                let assert_arg =
+                    bounds.require(field_ty, quote! { #support::IsArgumentFlag }, &assert_name);
+                let flag_name = flag_name.to_expr(container);
+                let append = container.append_call(
+                    quote! { #support::FlagArg::new(#flag_name, #field_ref) },
+                    receiver_name,
+                );
                 quote! {
                     #assert_arg
-                    ::unclap_support::FlagArg::new(&#flag_name, &#self_name.#field_name)
-                        .append_to(#receiver_name);
+                    #append
                 }
             }
-            None => quote_spanned! { field.variant.span()=>
-                compile_error!("Can not use #[argument(variant(flag))] on an unnamed field without providing a name");
-            },
-        },
-    }
-}
-
-fn append_fields<'a, I: 'a + IntoIterator<Item = &'a Field>>(
-    fields: I,
-    self_name: &'a Ident,
-    receiver_name: &'a Ident,
-) -> impl 'a + Iterator<Item = TokenStream> {
-    fields.into_iter().enumerate().map(move |(idx, field)| {
-        let parsed_field = UnclapField::from_field(&field);
-        let member_name = match field.ident.as_ref() {
-            Some(name) => Member::Named(name.clone()),
-            None => Member::Unnamed(idx.into()),
-        };
-
-        match parsed_field {
-            Ok(parsed_field) => {
-                wrapped_field(&parsed_field, &member_name, self_name, receiver_name)
+        }
+        FieldVariant::Count(NameOrOptions(options)) => {
+            let flag_name = match container.flag_name_for_field(field, options.name.as_ref()) {
+                Some(flag_name) => flag_name.to_expr(container),
+                None => return missing_flag_name(field, "count"),
+            };
+            let assert_arg =
+                bounds.require(field_ty, quote! { #support::IsArgumentCount }, &assert_name);
+            let negative = options.negative.as_ref().map(|negative| {
+                quote! { .with_negative(#negative) }
+            });
+            let bundle = options.bundle;
+            let append = container.append_call(
+                quote! {
+                    #support::CountArg::new(#flag_name, #field_ref)
+                        #negative
+                        .bundled(#bundle)
+                },
+                receiver_name,
+            );
+            quote! {
+                #assert_arg
+                #append
             }
-            Err(e) => e.write_errors(),
         }
-    })
+    }
 }
 
-fn variant_prelude(variant: &UnclapVariant, receiver_name: &Ident) -> TokenStream {
-    match &*variant.variant {
-        EnumVariant::Unnamed(..) => quote! {},
-        EnumVariant::Named(name) => {
-            let flag_name = flag_name_for_variant(variant, name);
-            quote! {
-                #flag_name.append_to(#receiver_name);
+/// Appends a single field, after the `--` separator for a `last` field. The separator
+/// is left out when the field passes no arguments.
+fn separated_field(
+    field: &UnclapField,
+    field_name: &Member,
+    field_ref: &TokenStream,
+    receiver_name: &Ident,
+    container: &Container,
+    bounds: &mut ImplBounds,
+) -> TokenStream {
+    let append = wrapped_field(
+        field,
+        field_name,
+        field_ref,
+        receiver_name,
+        container,
+        bounds,
+    );
+    if field.last {
+        let support = &container.support;
+        let arg_type = &container.arg_type;
+        let separated = Ident::new("__unclap_separated", Span::mixed_site());
+        quote! {
+            let mut #separated = #support::Separated::<#arg_type, _>::new(#receiver_name);
+            {
+                let #receiver_name = &mut #separated;
+                #append
             }
+            #separated.finish("--");
         }
+    } else {
+        append
     }
 }
 
-enum TupleMatchStyle {
-    StyleUnit,
-    StyleSingleTuple(UnclapField),
-    NotAllowed(TokenStream),
+/// The name of a field in errors, its flag name as passed if it has one
+fn field_display_name(
+    field: &UnclapField,
+    field_name: &Member,
+    container: &Container,
+) -> TokenStream {
+    let flag_name = match &*field.variant {
+        FieldVariant::Named(NameOrOptions(options)) => container.named_flag_name(field, options),
+        FieldVariant::Flag(NameOrOptions(options)) => {
+            container.flag_name_for_field(field, options.name.as_ref())
+        }
+        FieldVariant::Count(NameOrOptions(options)) => {
+            container.flag_name_for_field(field, options.name.as_ref())
+        }
+        FieldVariant::Unnamed(..) => None,
+    };
+    let name = match (flag_name, field_name) {
+        (Some(flag_name), _) => return flag_name.to_expr(container),
+        (None, Member::Named(ident)) => ident.unraw().to_string(),
+        (None, Member::Unnamed(index)) => index.index.to_string(),
+    };
+    quote! { #name }
 }
 
-fn unclap_for_variant(
-    enum_name: &Ident,
-    variant: &UnclapVariant,
+/// Guards `append` by the versions of the program supporting the field. For other
+/// versions, the field is passed with its `fallback` name, left out or reported.
+#[allow(clippy::too_many_arguments)]
+fn version_gated_field(
+    syn_field: &Field,
+    field: &UnclapField,
+    field_name: &Member,
+    field_ref: &TokenStream,
     receiver_name: &Ident,
+    container: &Container,
+    bounds: &mut ImplBounds,
+    append: TokenStream,
 ) -> TokenStream {
-    let name = &variant.ident;
-    let self_name = Ident::new("the_v", Span::call_site());
-    let (tuple_match, style) = match variant.fields.style {
-        darling::ast::Style::Unit => (quote! {}, TupleMatchStyle::StyleUnit),
-        darling::ast::Style::Tuple => {
-            if variant.fields.len() != 1 {
-                let user_error = quote_spanned! {variant.span()=>
-                    compile_error!("Enum variants with tuple structs must have exactly one field")
+    if field.since.is_none() && field.until.is_none() {
+        if field.fallback.is_some() || !matches!(field.unsupported, Unsupported::Error) {
+            return quote_spanned! { syn_field.span()=>
+                compile_error!("`fallback` and `unsupported` apply to fields with `since` or `until`");
+            };
+        }
+        return append;
+    }
+    let krate = &container.krate;
+    let support = &container.support;
+    let arg_type = &container.arg_type;
+    let unsupported = Ident::new("__unclap_unsupported", Span::mixed_site());
+    let otherwise = match (&field.fallback, &field.unsupported) {
+        (Some(fallback), Unsupported::Error) => {
+            // The fallback is the same field, passed with a different flag name
+            let mut fallback_field = match UnclapField::from_field(syn_field) {
+                Ok(fallback_field) => fallback_field,
+                Err(e) => return e.write_errors(),
+            };
+            if !fallback_field.set_flag_name(fallback.clone()) {
+                return quote_spanned! { fallback.span()=>
+                    compile_error!("A `fallback` name applies to flag, count and named fields");
                 };
-                (quote! { (..) }, TupleMatchStyle::NotAllowed(user_error))
-            } else {
-                let field = UnclapField::from_field(&variant.fields.fields[0]);
-                match field {
-                    Ok(parsed_field) => (
-                        quote! { ( #self_name ) },
-                        TupleMatchStyle::StyleSingleTuple(parsed_field),
-                    ),
-                    Err(bad_field) => (
-                        quote! { {..} },
-                        TupleMatchStyle::NotAllowed(bad_field.write_errors()),
-                    ),
-                }
             }
+            separated_field(
+                &fallback_field,
+                field_name,
+                field_ref,
+                receiver_name,
+                container,
+                bounds,
+            )
         }
-        darling::ast::Style::Struct => {
-            let user_error = quote_spanned! {variant.span()=>
-                compile_error!("Enum variants with named structs are not supported")
+        (Some(fallback), Unsupported::Skip) => {
+            return quote_spanned! { fallback.span()=>
+                compile_error!("Can not use both `fallback` and `unsupported = \"skip\"`");
             };
-            (quote! { {..} }, TupleMatchStyle::NotAllowed(user_error))
+        }
+        (None, Unsupported::Skip) => TokenStream::new(),
+        // Only a field that passes anything is reported
+        (None, Unsupported::Error) => {
+            let probe = Ident::new("__unclap_probe", Span::mixed_site());
+            quote! {
+                let mut #probe = #support::Probe::default();
+                {
+                    let #receiver_name = &mut #probe;
+                    #append
+                }
+                if #probe.received() {
+                    #krate::ArgumentReceiver::<#arg_type>::unsupported_arg(#receiver_name, #unsupported);
+                }
+            }
         }
     };
-    match style {
-        TupleMatchStyle::NotAllowed(tuple_error) => quote! {
-            #enum_name :: #name #tuple_match => #tuple_error,
-        },
-        TupleMatchStyle::StyleSingleTuple(field) => {
-            let field_ty = &field.ty;
-            let assert_name = make_assert_impl_name(&field, &Member::Unnamed(0.into()));
-            let assert_arg = quote_spanned! {variant.span()=>
-                #[allow(dead_code)]
-                struct #assert_name where #field_ty: ::unclap_core::Argument;
-            };
-            let append_field = quote! {
-                #assert_arg
-                <#field_ty as ::unclap_core::Argument>::append_to(&#self_name, #receiver_name);
-            };
+    let version = |version: &Option<VersionLit>| match version {
+        Some(VersionLit { parts }) => quote! { ::std::option::Option::Some(&[ #( #parts ),* ]) },
+        None => quote! { ::std::option::Option::None },
+    };
+    let since = version(&field.since);
+    let until = version(&field.until);
+    let name = field_display_name(field, field_name, container);
+    quote! {
+        match #support::VersionGate::new(#name, #since, #until)
+            .check(#krate::ArgumentReceiver::<#arg_type>::target_version(#receiver_name))
+        {
+            ::std::result::Result::Ok(()) => {
+                #append
+            }
+            #[allow(unused_variables)]
+            ::std::result::Result::Err(#unsupported) => {
+                #otherwise
+            }
+        }
+    }
+}
 
-            let prelude = variant_prelude(&variant, &receiver_name);
-            quote! {
-                #enum_name :: #name #tuple_match => {
-                    #prelude
-                    #append_field
-                },
+/// Guards `append` by the conditions under which the field is left out
+fn conditional_field(
+    field: &UnclapField,
+    field_name: &Member,
+    field_ref: &TokenStream,
+    container: &Container,
+    bounds: &mut ImplBounds,
+    append: TokenStream,
+) -> TokenStream {
+    let mut assert_eq = TokenStream::new();
+    let mut conditions = Vec::new();
+    if let Some(DefaultValue(default)) = &field.default {
+        // The value of an optional field is compared, an absent field is left out anyway
+        conditions.push(match types::option_inner(&field.ty) {
+            Some(_) => {
+                let value = make_value_name();
+                quote! {
+                    match #field_ref {
+                        ::std::option::Option::Some(#value) => {
+                            ::std::cmp::PartialEq::ne(#value, &(#default))
+                        }
+                        ::std::option::Option::None => true,
+                    }
+                }
+            }
+            None => quote! { ::std::cmp::PartialEq::ne(#field_ref, &(#default)) },
+        });
+    } else if let Some(defaults) = &container.defaults_name {
+        let assert_name = make_field_ident("__unclap_assert_eq", field_name);
+        assert_eq = bounds.require(&field.ty, quote! { ::std::cmp::PartialEq }, &assert_name);
+        conditions.push(quote! { ::std::cmp::PartialEq::ne(#field_ref, &#defaults.#field_name) });
+    }
+    if let Some(skip_if) = &field.skip_if {
+        conditions.push(quote! { !#skip_if(#field_ref) });
+    }
+    if conditions.is_empty() {
+        append
+    } else {
+        quote! {
+            #assert_eq
+            if #( #conditions )&&* {
+                #append
             }
         }
-        TupleMatchStyle::StyleUnit => {
-            let prelude = variant_prelude(&variant, &receiver_name);
-            quote! {
-                #enum_name :: #name #tuple_match => {
-                    #prelude
-                },
+    }
+}
+
+/// The position of a field among the fields of its struct or variant, smallest first
+#[derive(PartialEq, Eq, PartialOrd, Ord, Default)]
+struct FieldOrder {
+    subcommand: bool,
+    last: bool,
+    order: i32,
+    positional: bool,
+}
+
+impl FieldOrder {
+    fn of(field: &UnclapField, container: &Container) -> Self {
+        FieldOrder {
+            subcommand: field.subcommand,
+            last: field.last,
+            order: field.order,
+            positional: container.options_first && field.is_positional(),
+        }
+    }
+}
+
+/// Appends all fields in order. `field_ref` gives the expression referencing a field.
+/// Fields are sorted stably by their [`FieldOrder`].
+fn append_fields<'a, I: IntoIterator<Item = &'a Field>>(
+    fields: I,
+    field_ref: impl Fn(&Member) -> TokenStream,
+    receiver_name: &Ident,
+    container: &Container,
+    bounds: &mut ImplBounds,
+    mut flag_names: FlagNames,
+) -> Vec<TokenStream> {
+    let mut has_subcommand = false;
+    let mut has_stdin = false;
+    let mut has_last = false;
+    let mut fields: Vec<(FieldOrder, TokenStream)> = fields
+        .into_iter()
+        .enumerate()
+        .map(|(idx, field)| {
+            let parsed_field = UnclapField::from_field(field);
+            let member_name = match field.ident.as_ref() {
+                Some(name) => Member::Named(name.clone()),
+                None => Member::Unnamed(idx.into()),
+            };
+
+            match parsed_field {
+                Ok(parsed_field) if parsed_field.skip => {
+                    (FieldOrder::default(), TokenStream::new())
+                }
+                Ok(parsed_field)
+                    if parsed_field.subcommand && replace(&mut has_subcommand, true) =>
+                {
+                    let error = quote_spanned! { field.span()=>
+                        compile_error!("Only a single field can hold a subcommand");
+                    };
+                    (FieldOrder::default(), error)
+                }
+                Ok(parsed_field) if parsed_field.stdin && replace(&mut has_stdin, true) => {
+                    let error = quote_spanned! { field.span()=>
+                        compile_error!("Only a single field can be fed to stdin");
+                    };
+                    (FieldOrder::default(), error)
+                }
+                Ok(parsed_field) if parsed_field.last && replace(&mut has_last, true) => {
+                    let error = quote_spanned! { field.span()=>
+                        compile_error!("Only a single field can be passed after `--`");
+                    };
+                    (FieldOrder::default(), error)
+                }
+                // The subcommand would receive the arguments after the `--` separator
+                Ok(parsed_field)
+                    if (parsed_field.subcommand && has_last)
+                        || (parsed_field.last && has_subcommand) =>
+                {
+                    let error = quote_spanned! { field.span()=>
+                        compile_error!("A `last` field can not be used together with a `subcommand` field");
+                    };
+                    (FieldOrder::default(), error)
+                }
+                Ok(parsed_field) => {
+                    let duplicates = flag_names.claim_field(&parsed_field, &member_name, container);
+                    let field_ref = field_ref(&member_name);
+                    let append = separated_field(
+                        &parsed_field,
+                        &member_name,
+                        &field_ref,
+                        receiver_name,
+                        container,
+                        bounds,
+                    );
+                    let append = version_gated_field(
+                        field,
+                        &parsed_field,
+                        &member_name,
+                        &field_ref,
+                        receiver_name,
+                        container,
+                        bounds,
+                        append,
+                    );
+                    let append = conditional_field(
+                        &parsed_field,
+                        &member_name,
+                        &field_ref,
+                        container,
+                        bounds,
+                        append,
+                    );
+                    let append = quote! {
+                        #duplicates
+                        #append
+                    };
+                    (FieldOrder::of(&parsed_field, container), append)
+                }
+                Err(e) => (FieldOrder::default(), e.write_errors()),
             }
+        })
+        .collect();
+    fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+    fields.into_iter().map(|(_, append)| append).collect()
+}
+
+fn variant_prelude(
+    variant: &UnclapVariant,
+    receiver_name: &Ident,
+    container: &Container,
+) -> TokenStream {
+    match variant.variant() {
+        EnumVariant::Unnamed(..) => quote! {},
+        EnumVariant::Named(name) => {
+            let flag_name = container
+                .flag_name_for_variant(variant, name)
+                .to_expr(container);
+            container.append_call(flag_name, receiver_name)
+        }
+        EnumVariant::Subcommand(..) => {
+            let subcommand = container.subcommand_name_for_variant(variant);
+            container.append_call(subcommand.into_token_stream(), receiver_name)
         }
     }
 }
 
-fn append_variant_arms<'a, I: 'a + IntoIterator<Item = &'a Variant>>(
-    enum_name: &'a Ident,
-    variants: I,
-    receiver_name: &'a Ident,
-) -> impl 'a + Iterator<Item = TokenStream> {
-    variants.into_iter().map(move |v| {
-        let parsed_var = UnclapVariant::from_variant(&v);
+fn unclap_for_variant(
+    enum_name: &Ident,
+    variant: &UnclapVariant,
+    receiver_name: &Ident,
+    container: &Container,
+    bounds: &mut ImplBounds,
+) -> TokenStream {
+    let pattern = variant_pattern(enum_name, variant);
+    let prelude = variant_prelude(variant, receiver_name, container);
+    let mut flag_names = FlagNames::default();
+    if let EnumVariant::Named(name) = variant.variant() {
+        let flag_name = container.flag_name_for_variant(variant, name);
+        flag_names.claim(&flag_name, variant.ident.span(), &variant.ident);
+    }
+    let fields = append_fields(
+        &variant.fields.fields,
+        |member| make_binding_name(member).into_token_stream(),
+        receiver_name,
+        container,
+        bounds,
+        flag_names,
+    );
+    quote! {
+        #pattern => {
+            #prelude
+            #( #fields )*
+        },
+    }
+}
 
-        match parsed_var {
-            Ok(parsed_var) => unclap_for_variant(enum_name, &parsed_var, receiver_name),
-            Err(e) => e.write_errors(),
+/// The pattern matching a variant, binding each field to its [`make_binding_name`]
+fn variant_pattern(enum_name: &Ident, variant: &UnclapVariant) -> TokenStream {
+    let name = &variant.ident;
+    let members: Vec<Member> = variant
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| match field.ident.as_ref() {
+            Some(name) => Member::Named(name.clone()),
+            None => Member::Unnamed(idx.into()),
+        })
+        .collect();
+    let bindings = members.iter().map(make_binding_name);
+    match variant.fields.style {
+        darling::ast::Style::Unit => quote! { #enum_name :: #name },
+        darling::ast::Style::Tuple => quote! { #enum_name :: #name ( #( #bindings ),* ) },
+        darling::ast::Style::Struct => {
+            quote! { #enum_name :: #name { #( #members: #bindings ),* } }
         }
-    })
+    }
+}
+
+fn append_variant_arms<'a, I: IntoIterator<Item = &'a Variant>>(
+    enum_name: &Ident,
+    variants: I,
+    receiver_name: &Ident,
+    container: &Container,
+    bounds: &mut ImplBounds,
+) -> Vec<TokenStream> {
+    variants
+        .into_iter()
+        .map(|v| {
+            let parsed_var = UnclapVariant::from_variant(v);
+
+            match parsed_var {
+                Ok(parsed_var) => {
+                    unclap_for_variant(enum_name, &parsed_var, receiver_name, container, bounds)
+                }
+                Err(e) => {
+                    let name = &v.ident;
+                    let errors = e.write_errors();
+                    quote! {
+                        #enum_name :: #name { .. } => { #errors }
+                    }
+                }
+            }
+        })
+        .collect()
 }
 
 fn unclap_for_struct(
     name: &Ident,
+    generics: &Generics,
     fields: &Punctuated<Field, Comma>,
-    _attrs: &[Attribute],
+    container: &Container,
 ) -> TokenStream {
-    let self_name = Ident::new("the_self", Span::call_site());
-    let receiver_name = Ident::new("recv", Span::call_site());
+    let self_name = Ident::new("the_self", Span::mixed_site());
+    let receiver_name = Ident::new("recv", Span::mixed_site());
 
-    let fields = append_fields(fields, &self_name, &receiver_name);
-
-    quote! {
-        impl ::unclap_core::Argument for #name {
-            fn append_to<R: ::unclap_core::ArgumentReceiver>(&self, #receiver_name: &mut R) {
-                let #self_name = self;
-                #( #fields )*
-            }
+    let mut bounds = ImplBounds::new(generics);
+    let fields = append_fields(
+        fields,
+        |member| quote! { &#self_name.#member },
+        &receiver_name,
+        container,
+        &mut bounds,
+        FlagNames::default(),
+    );
+    let defaults = container.defaults_name.as_ref().map(|defaults| {
+        bounds.require_impl(parse_quote!(Self: ::std::default::Default));
+        quote! {
+            let #defaults = <Self as ::std::default::Default>::default();
         }
-    }
+    });
+    let body = quote! {
+        let #self_name = self;
+        #defaults
+        #( #fields )*
+    };
+    argument_impls(name, bounds, &receiver_name, container, body)
 }
 
 fn unclap_for_enum(
     enum_name: &Ident,
+    generics: &Generics,
     variants: &Punctuated<syn::Variant, Comma>,
-    _attrs: &[Attribute],
+    container: &Container,
+) -> TokenStream {
+    if container.defaults_name.is_some() {
+        abort_call_site!("`omit_defaults` only supports structs");
+    }
+    let receiver_name = Ident::new("recv", Span::mixed_site());
+    let mut bounds = ImplBounds::new(generics);
+    let matches = append_variant_arms(enum_name, variants, &receiver_name, container, &mut bounds);
+    let body = quote! {
+        match self {
+            #( #matches )*
+        }
+    };
+    argument_impls(enum_name, bounds, &receiver_name, container, body)
+}
+
+/// Implements `Argument` by delegating to the implementation of `FlattenArgument`,
+/// which is given by `body`.
+fn argument_impls(
+    name: &Ident,
+    bounds: ImplBounds,
+    receiver_name: &Ident,
+    container: &Container,
+    body: TokenStream,
 ) -> TokenStream {
-    let receiver_name = Ident::new("recv", Span::call_site());
-    let matches = append_variant_arms(enum_name, variants, &receiver_name);
+    let generics = bounds.into_generics();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let flag_prefix = &container.flag_prefix_name;
+    let krate = &container.krate;
+    let arg_type = &container.arg_type;
+    let argument = container.argument_trait();
+    let flatten = container.flatten_trait();
 
     quote! {
-        impl ::unclap_core::Argument for #enum_name {
-            fn append_to<R: ::unclap_core::ArgumentReceiver>(&self, #receiver_name: &mut R) {
-                match self {
-                    #( #matches )*
-                }
+        impl #impl_generics #argument for #name #ty_generics #where_clause {
+            fn append_to<__UnclapR: #krate::ArgumentReceiver<#arg_type>>(&self, #receiver_name: &mut __UnclapR) {
+                <Self as #flatten>::append_prefixed(self, "", #receiver_name);
+            }
+        }
+
+        impl #impl_generics #flatten for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn append_prefixed<__UnclapR: #krate::ArgumentReceiver<#arg_type>>(&self, #flag_prefix: &str, #receiver_name: &mut __UnclapR) {
+                #body
             }
         }
     }
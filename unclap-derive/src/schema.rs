@@ -0,0 +1,206 @@
+use crate::attrs::{
+    doc_comment, EnumVariant, FieldVariant, Multiple, NameOrOptions, UnclapField, UnclapVariant,
+};
+use crate::bounds::ImplBounds;
+use crate::{make_assert_impl_name, types, Container};
+use darling::{FromField, FromVariant};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    ext::IdentExt, punctuated::Punctuated, token::Comma, Field, Generics, Ident, LitStr, Member,
+    Variant,
+};
+
+fn option_str(value: Option<String>) -> TokenStream {
+    match value {
+        Some(value) => quote! { ::std::option::Option::Some(#value) },
+        None => quote! { ::std::option::Option::None },
+    }
+}
+
+/// The `FieldInfo` of a field, or nothing for skipped fields
+fn field_info(
+    field: &UnclapField,
+    field_name: &Member,
+    container: &Container,
+    bounds: &mut ImplBounds,
+) -> Option<TokenStream> {
+    if field.skip {
+        return None;
+    }
+    let krate = &container.krate;
+    let name = match field_name {
+        Member::Named(ident) => ident.unraw().to_string(),
+        Member::Unnamed(index) => index.index.to_string(),
+    };
+    let value_ty = types::option_inner(&field.ty);
+    let optional = value_ty.is_some();
+    let value_ty = value_ty.unwrap_or(&field.ty);
+    let mut repeated = field.raw || types::is_collection(value_ty);
+    // Flattened fields and subcommands are described by their own schema
+    let flattened = if field.flatten.is_some() || field.subcommand {
+        let field_ty = &field.ty;
+        let assert_name = make_assert_impl_name(field, field_name);
+        let assert_schema =
+            bounds.require(field_ty, quote! { #krate::ArgumentSchema }, &assert_name);
+        quote! {
+            ::std::option::Option::Some({
+                #assert_schema
+                <#field_ty as #krate::ArgumentSchema>::schema()
+            })
+        }
+    } else {
+        quote! { ::std::option::Option::None }
+    };
+    let (kind, flag) = if let Some(flatten) = &field.flatten {
+        let prefix = flatten.as_ref().explicit().and_then(|f| f.prefix.as_ref());
+        (quote! { Flatten }, prefix.map(|prefix| prefix.value()))
+    } else if let Some(NameOrOptions(env)) = &field.env {
+        // A collection is joined into a single variable
+        repeated = false;
+        (quote! { Env }, env.name.as_ref().map(LitStr::value))
+    } else if field.stdin {
+        // Bytes are a single payload
+        repeated = false;
+        (quote! { Stdin }, None)
+    } else if field.subcommand {
+        (quote! { Subcommand }, None)
+    } else {
+        match &*field.variant {
+            FieldVariant::Unnamed(..) => (quote! { Positional }, None),
+            FieldVariant::Named(NameOrOptions(options)) => {
+                repeated |=
+                    options.key_value.is_some() || !matches!(options.multiple, Multiple::Once);
+                let flag = container.named_flag_name(field, options);
+                (quote! { Named }, flag.map(|flag| flag.unprefixed()))
+            }
+            FieldVariant::Flag(NameOrOptions(options)) => {
+                let flag = container.flag_name_for_field(field, options.name.as_ref());
+                (quote! { Flag }, flag.map(|flag| flag.unprefixed()))
+            }
+            FieldVariant::Count(NameOrOptions(options)) => {
+                repeated = false;
+                let flag = container.flag_name_for_field(field, options.name.as_ref());
+                (quote! { Count }, flag.map(|flag| flag.unprefixed()))
+            }
+        }
+    };
+    let flag = option_str(flag);
+    let value_type = types::type_name(value_ty);
+    let doc = option_str(doc_comment(&field.attrs));
+    Some(quote! {
+        #krate::FieldInfo {
+            name: #name,
+            flag: #flag,
+            kind: #krate::FieldKind::#kind,
+            value_type: #value_type,
+            optional: #optional,
+            repeated: #repeated,
+            doc: #doc,
+            flattened: #flattened,
+        }
+    })
+}
+
+/// The `FieldInfo`s of all fields in declaration order. Fields that fail to parse
+/// are left out, the error is reported by the `Argument` implementation.
+fn field_infos<'a, I: IntoIterator<Item = &'a Field>>(
+    fields: I,
+    container: &Container,
+    bounds: &mut ImplBounds,
+) -> Vec<TokenStream> {
+    fields
+        .into_iter()
+        .enumerate()
+        .filter_map(|(idx, field)| {
+            let parsed_field = UnclapField::from_field(field).ok()?;
+            let member_name = match field.ident.as_ref() {
+                Some(name) => Member::Named(name.clone()),
+                None => Member::Unnamed(idx.into()),
+            };
+            field_info(&parsed_field, &member_name, container, bounds)
+        })
+        .collect()
+}
+
+fn variant_info(
+    variant: &UnclapVariant,
+    container: &Container,
+    bounds: &mut ImplBounds,
+) -> TokenStream {
+    let krate = &container.krate;
+    let name = variant.ident.unraw().to_string();
+    let (kind, flag) = match variant.variant() {
+        EnumVariant::Unnamed(..) => (quote! { Unnamed }, None),
+        EnumVariant::Named(name) => {
+            let flag = container.flag_name_for_variant(variant, name);
+            (quote! { Named }, Some(flag.unprefixed()))
+        }
+        EnumVariant::Subcommand(..) => {
+            let subcommand = container.subcommand_name_for_variant(variant);
+            (quote! { Subcommand }, Some(subcommand.value()))
+        }
+    };
+    let flag = option_str(flag);
+    let doc = option_str(doc_comment(&variant.attrs));
+    let fields = field_infos(&variant.fields.fields, container, bounds);
+    quote! {
+        #krate::VariantInfo {
+            name: #name,
+            flag: #flag,
+            kind: #krate::VariantKind::#kind,
+            doc: #doc,
+            fields: ::std::vec![ #( #fields ),* ],
+        }
+    }
+}
+
+fn schema_impl(
+    name: &Ident,
+    bounds: ImplBounds,
+    container: &Container,
+    fields: Vec<TokenStream>,
+    variants: Vec<TokenStream>,
+) -> TokenStream {
+    let generics = bounds.into_generics();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let krate = &container.krate;
+    let doc = option_str(container.doc.clone());
+    quote! {
+        impl #impl_generics #krate::ArgumentSchema for #name #ty_generics #where_clause {
+            fn schema() -> #krate::Schema {
+                #krate::Schema {
+                    doc: #doc,
+                    fields: ::std::vec![ #( #fields ),* ],
+                    variants: ::std::vec![ #( #variants ),* ],
+                }
+            }
+        }
+    }
+}
+
+pub fn schema_for_struct(
+    name: &Ident,
+    generics: &Generics,
+    fields: &Punctuated<Field, Comma>,
+    container: &Container,
+) -> TokenStream {
+    let mut bounds = ImplBounds::new(generics);
+    let fields = field_infos(fields, container, &mut bounds);
+    schema_impl(name, bounds, container, fields, Vec::new())
+}
+
+pub fn schema_for_enum(
+    name: &Ident,
+    generics: &Generics,
+    variants: &Punctuated<Variant, Comma>,
+    container: &Container,
+) -> TokenStream {
+    let mut bounds = ImplBounds::new(generics);
+    let variants = variants
+        .iter()
+        .filter_map(|variant| UnclapVariant::from_variant(variant).ok())
+        .map(|variant| variant_info(&variant, container, &mut bounds))
+        .collect();
+    schema_impl(name, bounds, container, Vec::new(), variants)
+}
@@ -0,0 +1,66 @@
+use syn::{GenericArgument, PathArguments, Type};
+
+/// The type `T` if `ty` is spelled as `Option<T>`.
+///
+/// The derive only sees the tokens of a type, so this can not see through
+/// type aliases and might be fooled by a different type named `Option`.
+pub fn option_inner(ty: &Type) -> Option<&Type> {
+    let path = match ty {
+        Type::Path(path) if path.qself.is_none() => &path.path,
+        Type::Group(group) => return option_inner(&group.elem),
+        Type::Paren(paren) => return option_inner(&paren.elem),
+        _ => return None,
+    };
+    let last = path.segments.last()?;
+    if last.ident != "Option" {
+        return None;
+    }
+    match &last.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => match &args.args[0] {
+            GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Whether `ty` is spelled as one of the standard collections, an array or a slice.
+///
+/// Like [`option_inner`], this only sees the tokens of the type.
+pub fn is_collection(ty: &Type) -> bool {
+    const COLLECTIONS: &[&str] = &[
+        "Vec", "VecDeque", "BTreeSet", "HashSet", "BTreeMap", "HashMap",
+    ];
+    match ty {
+        Type::Path(path) if path.qself.is_none() => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|last| COLLECTIONS.iter().any(|name| last.ident == name)),
+        Type::Reference(reference) => is_collection(&reference.elem),
+        Type::Group(group) => is_collection(&group.elem),
+        Type::Paren(paren) => is_collection(&paren.elem),
+        Type::Array(_) | Type::Slice(_) => true,
+        _ => false,
+    }
+}
+
+/// The type as written, with spaces only between words and after commas,
+/// as in `HashMap<&'a str, u32>`
+pub fn type_name(ty: &Type) -> String {
+    let spaced = quote::quote!(#ty).to_string();
+    let chars: Vec<char> = spaced.chars().collect();
+    let is_word = |c: Option<&char>| c.is_some_and(|c| c.is_alphanumeric() || *c == '_');
+    let mut name = String::with_capacity(chars.len());
+    for (idx, c) in chars.iter().enumerate() {
+        let last = name.chars().last();
+        if *c == ' '
+            && last != Some(',')
+            && !(is_word(last.as_ref()) && is_word(chars.get(idx + 1)))
+        {
+            continue;
+        }
+        name.push(*c);
+    }
+    name
+}
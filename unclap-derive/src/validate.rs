@@ -0,0 +1,423 @@
+use crate::attrs::{unknown_name, RangeBound, RangeOptions, UnclapField, UnclapVariant};
+use crate::bounds::ImplBounds;
+use crate::{
+    conditional_field, make_binding_name, make_field_ident, optional_value, variant_pattern,
+    wrapped_field, Container,
+};
+use darling::{FromField, FromVariant};
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned, ToTokens};
+use syn::{
+    ext::IdentExt, parse_quote, punctuated::Punctuated, token::Comma, Field, Generics, Ident,
+    LitStr, Member, Variant,
+};
+
+/// A field that is checked by `validate`, skipped fields are never checked
+struct CheckedField {
+    field: UnclapField,
+    member: Member,
+    /// The name of the field in errors
+    name: String,
+}
+
+impl CheckedField {
+    fn refers_to(&self, other: &str) -> bool {
+        self.field
+            .conflicts_with
+            .0
+            .iter()
+            .chain(&self.field.requires.0)
+            .any(|name| name.value() == other)
+    }
+}
+
+fn errors_name() -> Ident {
+    Ident::new("__unclap_errors", Span::mixed_site())
+}
+
+/// Pushes a `ValidationError` of `field` to the errors
+fn push_error(field: &str, violation: TokenStream, container: &Container) -> TokenStream {
+    let krate = &container.krate;
+    let errors = errors_name();
+    quote! {
+        #errors.push(#krate::ValidationError {
+            field: #field,
+            violation: #krate::Violation::#violation,
+        });
+    }
+}
+
+/// Whether the field passes anything, found by passing it to a `Probe`. The `--`
+/// separator of a `last` field does not count.
+fn is_set(
+    checked: &CheckedField,
+    field_ref: &TokenStream,
+    container: &Container,
+    bounds: &mut ImplBounds,
+) -> TokenStream {
+    let support = &container.support;
+    let receiver_name = Ident::new("recv", Span::mixed_site());
+    let probe = Ident::new("__unclap_probe", Span::mixed_site());
+    let append = wrapped_field(
+        &checked.field,
+        &checked.member,
+        field_ref,
+        &receiver_name,
+        container,
+        bounds,
+    );
+    let append = conditional_field(
+        &checked.field,
+        &checked.member,
+        field_ref,
+        container,
+        bounds,
+        append,
+    );
+    quote! {
+        {
+            let mut #probe = #support::Probe::default();
+            {
+                let #receiver_name = &mut #probe;
+                #append
+            }
+            #probe.received()
+        }
+    }
+}
+
+/// Checks the value of a field, which is the content of an optional field
+fn value_checks(
+    checked: &CheckedField,
+    field_ref: &TokenStream,
+    container: &Container,
+    bounds: &mut ImplBounds,
+) -> TokenStream {
+    let krate = &container.krate;
+    let support = &container.support;
+    let field = &checked.field;
+    let name = &checked.name;
+    let (value_ty, value_ref) = optional_value(&field.ty, field_ref);
+    let mut checks = Vec::new();
+    // Flattened and subcommand fields check their own constraints
+    if field.flatten.is_some() || field.subcommand {
+        let field_ty = &field.ty;
+        let assert_name = make_field_ident("__unclap_assert_validate", &checked.member);
+        let assert_validate = bounds.require(field_ty, quote! { #krate::Validate }, &assert_name);
+        let errors = errors_name();
+        checks.push(quote! {
+            #assert_validate
+            if let ::std::result::Result::Err(nested) =
+                <#field_ty as #krate::Validate>::validate(#field_ref)
+            {
+                #errors.extend(nested.into_errors());
+            }
+        });
+    }
+    let mut value_checks = Vec::new();
+    if let Some(RangeOptions { min, max }) = &field.range {
+        let assert_name = make_field_ident("__unclap_assert_range", &checked.member);
+        let assert_ord = bounds.require(value_ty, quote! { ::std::cmp::PartialOrd }, &assert_name);
+        let mut conditions = Vec::new();
+        let mut bound_text = |bound: &Option<RangeBound>, compare: TokenStream| match bound {
+            Some(RangeBound { expr, text }) => {
+                conditions.push(quote! { ::std::cmp::PartialOrd::#compare(#value_ref, &(#expr)) });
+                quote! { ::std::option::Option::Some(#text) }
+            }
+            None => quote! { ::std::option::Option::None },
+        };
+        let min_text = bound_text(min, quote! { lt });
+        let max_text = bound_text(max, quote! { gt });
+        let push = push_error(
+            name,
+            quote! { OutOfRange { min: #min_text, max: #max_text } },
+            container,
+        );
+        value_checks.push(quote! {
+            #assert_ord
+            if false #( || #conditions )* {
+                #push
+            }
+        });
+    }
+    if field.non_empty {
+        let assert_name = make_field_ident("__unclap_assert_non_empty", &checked.member);
+        let assert_empty =
+            bounds.require(value_ty, quote! { #support::IsEmptyValue }, &assert_name);
+        let push = push_error(name, quote! { Empty }, container);
+        value_checks.push(quote! {
+            #assert_empty
+            if #support::IsEmptyValue::is_empty_value(#value_ref) {
+                #push
+            }
+        });
+    }
+    if let Some(pattern) = &field.regex {
+        let assert_name = make_field_ident("__unclap_assert_regex", &checked.member);
+        let assert_str = bounds.require(
+            value_ty,
+            quote! { ::std::convert::AsRef<str> },
+            &assert_name,
+        );
+        let push = push_error(name, quote! { Mismatch(#pattern) }, container);
+        let regex = regex_expr(pattern, container);
+        value_checks.push(quote! {
+            #assert_str
+            if !#regex.is_match(::std::convert::AsRef::<str>::as_ref(#value_ref)) {
+                #push
+            }
+        });
+    }
+    if !value_checks.is_empty() {
+        let value_checks = quote! { #( #value_checks )* };
+        checks.push(crate::if_present(&field.ty, field_ref, value_checks));
+    }
+    quote! { #( #checks )* }
+}
+
+/// The compiled regular expression, which is checked when deriving
+#[cfg(feature = "regex")]
+fn regex_expr(pattern: &LitStr, container: &Container) -> TokenStream {
+    if let Err(e) = regex::Regex::new(&pattern.value()) {
+        let message = e.to_string();
+        return quote_spanned! { pattern.span()=>
+            compile_error!(#message)
+        };
+    }
+    let support = &container.support;
+    quote! {
+        {
+            static PATTERN: ::std::sync::OnceLock<#support::regex::Regex> =
+                ::std::sync::OnceLock::new();
+            PATTERN.get_or_init(|| #support::regex::Regex::new(#pattern).unwrap())
+        }
+    }
+}
+
+#[cfg(not(feature = "regex"))]
+fn regex_expr(pattern: &LitStr, _container: &Container) -> TokenStream {
+    quote_spanned! { pattern.span()=>
+        compile_error!("The `regex` constraint requires the `regex` feature of unclap")
+    }
+}
+
+/// The checks of all fields of a struct or variant, pushing to the errors. Fields
+/// that fail to parse are left out, the error is reported by the `Argument` implementation.
+fn check_fields<'a, I: IntoIterator<Item = &'a Field>>(
+    fields: I,
+    field_ref: impl Fn(&Member) -> TokenStream,
+    container: &Container,
+    bounds: &mut ImplBounds,
+) -> TokenStream {
+    let checked: Vec<CheckedField> = fields
+        .into_iter()
+        .enumerate()
+        .filter_map(|(idx, field)| {
+            let parsed_field = UnclapField::from_field(field).ok()?;
+            let member = match field.ident.as_ref() {
+                Some(name) => Member::Named(name.clone()),
+                None => Member::Unnamed(idx.into()),
+            };
+            let name = match &member {
+                Member::Named(ident) => ident.unraw().to_string(),
+                Member::Unnamed(index) => index.index.to_string(),
+            };
+            Some(CheckedField {
+                field: parsed_field,
+                member,
+                name,
+            })
+        })
+        .filter(|checked| !checked.field.skip)
+        .collect();
+    let names: Vec<&str> = checked
+        .iter()
+        .map(|checked| checked.name.as_str())
+        .collect();
+    let find = |name: &LitStr| -> Result<&CheckedField, TokenStream> {
+        checked
+            .iter()
+            .find(|checked| checked.name == name.value())
+            .ok_or_else(|| {
+                unknown_name("field", &name.value(), &names)
+                    .with_span(name)
+                    .write_errors()
+            })
+    };
+
+    let mut checks = Vec::new();
+    // Whether each field that a constraint refers to is set
+    for field in &checked {
+        let referenced = field.field.one_of.is_some()
+            || !field.field.conflicts_with.0.is_empty()
+            || !field.field.requires.0.is_empty()
+            || checked.iter().any(|other| other.refers_to(&field.name));
+        if referenced {
+            let set_name = make_field_ident("__unclap_set_", &field.member);
+            let is_set = is_set(field, &field_ref(&field.member), container, bounds);
+            checks.push(quote! { let #set_name = #is_set; });
+        }
+    }
+    let set_name = |field: &CheckedField| make_field_ident("__unclap_set_", &field.member);
+    for field in &checked {
+        let this = set_name(field);
+        for other in &field.field.conflicts_with.0 {
+            match find(other) {
+                Ok(other_field) => {
+                    let that = set_name(other_field);
+                    let other_name = &other_field.name;
+                    let push = push_error(
+                        &field.name,
+                        quote! { ConflictsWith(#other_name) },
+                        container,
+                    );
+                    checks.push(quote! {
+                        if #this && #that {
+                            #push
+                        }
+                    });
+                }
+                Err(error) => checks.push(error),
+            }
+        }
+        for other in &field.field.requires.0 {
+            match find(other) {
+                Ok(other_field) => {
+                    let that = set_name(other_field);
+                    let other_name = &other_field.name;
+                    let push = push_error(&field.name, quote! { Requires(#other_name) }, container);
+                    checks.push(quote! {
+                        if #this && !#that {
+                            #push
+                        }
+                    });
+                }
+                Err(error) => checks.push(error),
+            }
+        }
+        checks.push(value_checks(
+            field,
+            &field_ref(&field.member),
+            container,
+            bounds,
+        ));
+    }
+    // Groups are checked in the order of their first field
+    let mut groups: Vec<String> = Vec::new();
+    for field in &checked {
+        if let Some(group) = &field.field.one_of {
+            if !groups.contains(&group.value()) {
+                groups.push(group.value());
+            }
+        }
+    }
+    for group in groups {
+        let members: Vec<&CheckedField> = checked
+            .iter()
+            .filter(|field| field.field.one_of.as_ref().map(LitStr::value) == Some(group.clone()))
+            .collect();
+        let sets = members.iter().map(|field| set_name(field));
+        let names = members.iter().map(|field| &field.name);
+        let set = Ident::new("__unclap_group", Span::mixed_site());
+        let push = push_error(&group, quote! { OneOf(#set) }, container);
+        checks.push(quote! {
+            let #set: ::std::vec::Vec<&'static str> = [ #( (#sets, #names) ),* ]
+                .iter()
+                .filter(|(set, _)| *set)
+                .map(|(_, name)| *name)
+                .collect();
+            if #set.len() != 1 {
+                #push
+            }
+        });
+    }
+    quote! { #( #checks )* }
+}
+
+fn validate_impl(
+    name: &Ident,
+    bounds: ImplBounds,
+    container: &Container,
+    body: TokenStream,
+) -> TokenStream {
+    let generics = bounds.into_generics();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let krate = &container.krate;
+    let flag_prefix = &container.flag_prefix_name;
+    let errors = errors_name();
+    quote! {
+        impl #impl_generics #krate::Validate for #name #ty_generics #where_clause {
+            #[allow(unused_variables, unused_mut)]
+            fn validate(&self) -> ::std::result::Result<(), #krate::ValidationErrors> {
+                let #flag_prefix: &str = "";
+                let mut #errors = ::std::vec::Vec::new();
+                #body
+                #krate::ValidationErrors::from_errors(#errors)
+            }
+        }
+    }
+}
+
+pub fn validate_for_struct(
+    name: &Ident,
+    generics: &Generics,
+    fields: &Punctuated<Field, Comma>,
+    container: &Container,
+) -> TokenStream {
+    let self_name = Ident::new("the_self", Span::mixed_site());
+    let mut bounds = ImplBounds::new(generics);
+    let checks = check_fields(
+        fields,
+        |member| quote! { &#self_name.#member },
+        container,
+        &mut bounds,
+    );
+    // Fields equal to their default are not set
+    let defaults = container.defaults_name.as_ref().map(|defaults| {
+        bounds.require_impl(parse_quote!(Self: ::std::default::Default));
+        quote! {
+            let #defaults = <Self as ::std::default::Default>::default();
+        }
+    });
+    let body = quote! {
+        let #self_name = self;
+        #defaults
+        #checks
+    };
+    validate_impl(name, bounds, container, body)
+}
+
+pub fn validate_for_enum(
+    name: &Ident,
+    generics: &Generics,
+    variants: &Punctuated<Variant, Comma>,
+    container: &Container,
+) -> TokenStream {
+    let mut bounds = ImplBounds::new(generics);
+    let arms: Vec<TokenStream> = variants
+        .iter()
+        .filter_map(|variant| UnclapVariant::from_variant(variant).ok())
+        .map(|variant| {
+            let pattern = variant_pattern(name, &variant);
+            let checks = check_fields(
+                &variant.fields.fields,
+                |member| make_binding_name(member).into_token_stream(),
+                container,
+                &mut bounds,
+            );
+            quote! {
+                #pattern => {
+                    #checks
+                }
+            }
+        })
+        .collect();
+    let body = quote! {
+        #[allow(unreachable_patterns)]
+        match self {
+            #( #arms )*
+            _ => {}
+        }
+    };
+    validate_impl(name, bounds, container, body)
+}
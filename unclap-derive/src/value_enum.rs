@@ -0,0 +1,89 @@
+use crate::attrs::{UnclapContainer, UnclapValueVariant};
+use crate::Container;
+use darling::{FromDeriveInput, FromVariant};
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::{Data, DeriveInput, LitStr};
+
+pub fn derive_argument_value(input: &DeriveInput) -> TokenStream {
+    let name = &input.ident;
+    let container = match UnclapContainer::from_derive_input(input) {
+        Ok(container) => Container::new(&container),
+        Err(e) => return e.write_errors(),
+    };
+    let variants = match input.data {
+        Data::Enum(ref e) => &e.variants,
+        _ => {
+            return quote_spanned! {name.span()=>
+                compile_error!("`#[derive(ArgumentValue)]` only supports enums");
+            }
+        }
+    };
+
+    let mut errors = Vec::new();
+    let mut idents = Vec::new();
+    let mut values = Vec::new();
+    for variant in variants {
+        match UnclapValueVariant::from_variant(variant) {
+            Ok(parsed) if parsed.fields.is_unit() => {
+                values.push(parsed.rename.clone().unwrap_or_else(|| {
+                    LitStr::new(
+                        &container.name_from_ident(&parsed.ident),
+                        parsed.ident.span(),
+                    )
+                }));
+                idents.push(parsed.ident);
+            }
+            Ok(parsed) => errors.push(quote_spanned! {parsed.ident.span()=>
+                compile_error!("`#[derive(ArgumentValue)]` only supports unit variants");
+            }),
+            Err(e) => errors.push(e.write_errors()),
+        }
+    }
+    if !errors.is_empty() {
+        return quote! { #( #errors )* };
+    }
+
+    let krate = &container.krate;
+    let support = &container.support;
+    quote! {
+        impl #support::ArgumentValue for #name {
+            const VALUES: &'static [&'static str] = &[ #( #values ),* ];
+            fn as_value(&self) -> &'static str {
+                match self {
+                    #( #name :: #idents => #values, )*
+                }
+            }
+        }
+
+        impl<__UnclapT: #krate::ArgumentType> #krate::Argument<__UnclapT> for #name {
+            fn append_to<__UnclapR: #krate::ArgumentReceiver<__UnclapT>>(&self, recv: &mut __UnclapR) {
+                #krate::ArgumentReceiver::one_arg(
+                    recv,
+                    <__UnclapT as #krate::ArgumentType>::from_text(#support::ArgumentValue::as_value(self)),
+                );
+            }
+        }
+
+        impl<__UnclapT: #krate::ArgumentType> #support::SingleArgument<__UnclapT> for #name {}
+
+        impl ::std::fmt::Display for #name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.write_str(#support::ArgumentValue::as_value(self))
+            }
+        }
+
+        impl ::std::str::FromStr for #name {
+            type Err = #support::ParseValueError;
+            fn from_str(value: &str) -> ::std::result::Result<Self, Self::Err> {
+                match value {
+                    #( #values => ::std::result::Result::Ok(#name :: #idents), )*
+                    _ => ::std::result::Result::Err(#support::ParseValueError::new(
+                        value,
+                        <Self as #support::ArgumentValue>::VALUES,
+                    )),
+                }
+            }
+        }
+    }
+}
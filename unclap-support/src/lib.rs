@@ -1,19 +1,47 @@
-use std::ffi::OsString;
-use unclap_core::{Argument, ArgumentReceiver, ArgumentReceiverExt};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use unclap_core::{
+    Argument, ArgumentReceiver, ArgumentType, RecordedArgument, StdinPayload, UnsupportedArgument,
+    Version,
+};
 
-// --flagname <arg>
-pub struct Named<'a, A: 'a> {
-    dashed_flag_name: &'static str,
+// --flagname <arg>, or nothing for a value without arguments
+pub struct Named<'a, A: 'a + ?Sized> {
+    dashed_flag_name: Cow<'a, str>,
     arg: &'a A,
+    style: NamedStyle,
 }
 
-impl<'a, A: 'a> Named<'a, A> {
-    pub fn new(dashed_flag_name: &'static str, arg: &'a A) -> Named<'a, A> {
+/// How the flag name and the value of a [`Named`] argument are joined
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Default)]
+pub enum NamedStyle {
+    /// `--name value`, as two arguments
+    #[default]
+    Separate,
+    /// `--name=value`, or `name=value` for operands such as dd's `if=/dev/zero`
+    Equals,
+    /// `-Ovalue`, as in `-O2` or `-I/usr/include`
+    Attached,
+}
+
+impl<'a, A: 'a + ?Sized> Named<'a, A> {
+    pub fn new<N: Into<Cow<'a, str>>>(dashed_flag_name: N, arg: &'a A) -> Named<'a, A> {
         Named {
-            dashed_flag_name,
+            dashed_flag_name: dashed_flag_name.into(),
             arg,
+            style: NamedStyle::Separate,
         }
     }
+
+    /// Join the name and the value into a single argument. A value passing several
+    /// arguments repeats the joined name for each, as in `--name=a --name=b`, and
+    /// a value passing none leaves out the name as well.
+    pub fn with_style(mut self, style: NamedStyle) -> Self {
+        self.style = style;
+        self
+    }
 }
 
 /// An argument receiver that expects exactly one argument.
@@ -23,11 +51,15 @@ pub struct SingleArg<ArgType> {
 
 impl<ArgType> ArgumentReceiver<ArgType> for SingleArg<ArgType> {
     fn one_arg(&mut self, arg: ArgType) {
-        let old = core::mem::replace(&mut self.arg, Some(arg));
-        match old {
-            Some(_) => panic!("Only a single argument was expected, not more"),
-            None => {}
-        };
+        if self.arg.replace(arg).is_some() {
+            panic!("Only a single argument was expected, not more");
+        }
+    }
+}
+
+impl<ArgType> Default for SingleArg<ArgType> {
+    fn default() -> Self {
+        SingleArg::new()
     }
 }
 
@@ -35,59 +67,805 @@ impl<ArgType> SingleArg<ArgType> {
     pub fn new() -> SingleArg<ArgType> {
         SingleArg { arg: None }
     }
-    pub fn finalize<R: ArgumentReceiver<ArgType>>(self) -> ArgType {
+    pub fn finalize(self) -> ArgType {
         self.arg.expect("Exactly one argument was expected")
     }
+    /// The argument, or nothing if none was passed
+    pub fn into_arg(self) -> Option<ArgType> {
+        self.arg
+    }
+}
+
+impl<'a, T: ArgumentType, A: Argument<T> + ?Sized> Argument<T> for Named<'a, A> {
+    fn append_to<R: ArgumentReceiver<T>>(&self, r: &mut R) {
+        let mut values: Vec<T> = Vec::new();
+        self.arg.append_to(&mut values);
+        let separator = match self.style {
+            NamedStyle::Separate => {
+                if !values.is_empty() {
+                    r.one_arg(T::from_text(&self.dashed_flag_name));
+                    r.multiple_args(values);
+                }
+                return;
+            }
+            NamedStyle::Equals => "=",
+            NamedStyle::Attached => "",
+        };
+        for value in values {
+            let mut joined = T::from_text(&self.dashed_flag_name);
+            joined.push_text(separator);
+            joined.push_arg(value);
+            r.one_arg(joined);
+        }
+    }
+}
+
+/// Arguments that are passed as they are, e.g. after collecting them from another receiver
+pub struct Verbatim<'a, ArgType>(pub &'a [ArgType]);
+
+impl<'a, T: Clone> Argument<T> for Verbatim<'a, T> {
+    fn append_to<R: ArgumentReceiver<T>>(&self, r: &mut R) {
+        r.multiple_args(self.0.iter().cloned());
+    }
+}
+
+/// The arguments of all items in a collection, joined by a delimiter into a
+/// single argument, as in `--features a,b,c` or `-cp a.jar:b.jar`.
+pub struct Delimited<'a, C: ?Sized> {
+    collection: &'a C,
+    delimiter: &'a str,
+}
+
+impl<'a, C: ?Sized> Delimited<'a, C>
+where
+    &'a C: IntoIterator,
+{
+    pub fn new(collection: &'a C, delimiter: &'a str) -> Self {
+        Delimited {
+            collection,
+            delimiter,
+        }
+    }
+
+    /// The joined argument, or `None` if the items pass no arguments at all
+    pub fn joined<T: ArgumentType>(&self) -> Option<T>
+    where
+        <&'a C as IntoIterator>::Item: Argument<T>,
+    {
+        let mut args: Vec<T> = Vec::new();
+        for item in self.collection {
+            item.append_to(&mut args);
+        }
+        let mut args = args.into_iter();
+        let mut joined = args.next()?;
+        for arg in args {
+            joined.push_text(self.delimiter);
+            joined.push_arg(arg);
+        }
+        Some(joined)
+    }
+}
+
+impl<'a, T: ArgumentType, C: ?Sized> Argument<T> for Delimited<'a, C>
+where
+    &'a C: IntoIterator,
+    <&'a C as IntoIterator>::Item: Argument<T>,
+{
+    fn append_to<R: ArgumentReceiver<T>>(&self, r: &mut R) {
+        if let Some(joined) = self.joined() {
+            r.one_arg(joined);
+        }
+    }
+}
+
+// key=value
+pub struct KeyValue<'a, K: ?Sized, V: ?Sized> {
+    key: &'a K,
+    value: &'a V,
+    separator: &'a str,
+}
+
+impl<'a, K: ?Sized, V: ?Sized> KeyValue<'a, K, V> {
+    pub fn new(key: &'a K, value: &'a V, separator: &'a str) -> Self {
+        KeyValue {
+            key,
+            value,
+            separator,
+        }
+    }
+}
+
+/// `key=value`, or only `key` for a value without an argument such as `None`,
+/// as in `-DNAME`. A key without an argument leaves out the pair.
+impl<'a, T, K, V> Argument<T> for KeyValue<'a, K, V>
+where
+    T: ArgumentType,
+    K: SingleArgument<T> + ?Sized,
+    V: SingleArgument<T> + ?Sized,
+{
+    fn append_to<R: ArgumentReceiver<T>>(&self, r: &mut R) {
+        let mut key = SingleArg::new();
+        self.key.append_to(&mut key);
+        let mut joined = match key.into_arg() {
+            Some(key) => key,
+            None => return,
+        };
+        let mut value = SingleArg::new();
+        self.value.append_to(&mut value);
+        if let Some(value) = value.into_arg() {
+            joined.push_text(self.separator);
+            joined.push_arg(value);
+        }
+        r.one_arg(joined);
+    }
+}
+
+/// Arguments that pass at most one argument, as required for the keys and values
+/// of `key_value` fields. Implemented by `#[derive(ArgumentValue)]`.
+pub trait SingleArgument<ArgType = OsString>: Argument<ArgType> {}
+
+impl<T: ArgumentType> SingleArgument<T> for str {}
+impl<T: ArgumentType> SingleArgument<T> for String {}
+impl SingleArgument<OsString> for OsStr {}
+impl SingleArgument<OsString> for OsString {}
+impl SingleArgument<OsString> for Path {}
+impl SingleArgument<OsString> for PathBuf {}
+
+#[cfg(unix)]
+impl SingleArgument<Vec<u8>> for OsStr {}
+#[cfg(unix)]
+impl SingleArgument<Vec<u8>> for OsString {}
+#[cfg(unix)]
+impl SingleArgument<Vec<u8>> for Path {}
+#[cfg(unix)]
+impl SingleArgument<Vec<u8>> for PathBuf {}
+
+macro_rules! single_argument {
+    ($($ty:ty),*) => {
+        $(
+            impl<T: ArgumentType> SingleArgument<T> for $ty {}
+        )*
+    };
+}
+
+single_argument!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+impl<T, A: SingleArgument<T> + ?Sized> SingleArgument<T> for &A {}
+impl<T, A: SingleArgument<T>> SingleArgument<T> for Option<A> {}
+
+/// Collections of key-value pairs, passed as `key=value` arguments
+pub trait KeyValuePairs {
+    type Key: ?Sized;
+    type Value: ?Sized;
+
+    /// The pairs in a deterministic order: insertion order for sequences, key order for maps
+    fn pairs(&self) -> Vec<(&Self::Key, &Self::Value)>;
+
+    fn key_values<'a>(&'a self, separator: &'a str) -> Vec<KeyValue<'a, Self::Key, Self::Value>> {
+        self.pairs()
+            .into_iter()
+            .map(|(key, value)| KeyValue::new(key, value, separator))
+            .collect()
+    }
+}
+
+impl<K, V> KeyValuePairs for BTreeMap<K, V> {
+    type Key = K;
+    type Value = V;
+
+    fn pairs(&self) -> Vec<(&K, &V)> {
+        self.iter().collect()
+    }
+}
+
+impl<K: Ord, V, S> KeyValuePairs for HashMap<K, V, S> {
+    type Key = K;
+    type Value = V;
+
+    fn pairs(&self) -> Vec<(&K, &V)> {
+        let mut pairs: Vec<_> = self.iter().collect();
+        pairs.sort_by_key(|(key, _)| *key);
+        pairs
+    }
+}
+
+impl<K, V> KeyValuePairs for [(K, V)] {
+    type Key = K;
+    type Value = V;
+
+    fn pairs(&self) -> Vec<(&K, &V)> {
+        self.iter().map(|(key, value)| (key, value)).collect()
+    }
+}
+
+impl<K, V> KeyValuePairs for Vec<(K, V)> {
+    type Key = K;
+    type Value = V;
+
+    fn pairs(&self) -> Vec<(&K, &V)> {
+        self[..].pairs()
+    }
+}
+
+impl<K, V, const N: usize> KeyValuePairs for [(K, V); N] {
+    type Key = K;
+    type Value = V;
+
+    fn pairs(&self) -> Vec<(&K, &V)> {
+        self[..].pairs()
+    }
+}
+
+/// Arguments supplied from outside the program, passed verbatim and marked
+/// with [`ArgumentReceiver::untrusted_arg`].
+pub struct Raw<'a, C: ?Sized> {
+    args: &'a C,
+}
+
+impl<'a, C: ?Sized> Raw<'a, C>
+where
+    &'a C: IntoIterator,
+{
+    pub fn new(args: &'a C) -> Self {
+        Raw { args }
+    }
+}
+
+/// Each item has to pass exactly one argument, as strings do
+impl<'a, T, C: ?Sized> Argument<T> for Raw<'a, C>
+where
+    &'a C: IntoIterator,
+    <&'a C as IntoIterator>::Item: Argument<T>,
+{
+    fn append_to<R: ArgumentReceiver<T>>(&self, r: &mut R) {
+        for arg in self.args {
+            let mut single = SingleArg::new();
+            arg.append_to(&mut single);
+            r.untrusted_arg(single.finalize());
+        }
+    }
+}
+
+/// A value passed in an environment variable rather than as arguments, with
+/// [`ArgumentReceiver::set_env`]. The arguments of the value are joined by spaces,
+/// as in `RUSTFLAGS`, and a value without arguments leaves the variable untouched.
+pub struct EnvVar<'a, A: ?Sized> {
+    name: &'a str,
+    value: &'a A,
+    remove_empty: bool,
+}
+
+impl<'a, A: ?Sized> EnvVar<'a, A> {
+    pub fn new(name: &'a str, value: &'a A) -> Self {
+        EnvVar {
+            name,
+            value,
+            remove_empty: false,
+        }
+    }
+
+    /// Remove the variable with [`ArgumentReceiver::remove_env`] for a value
+    /// without arguments, so that the program does not inherit it
+    pub fn removing_empty(mut self, remove_empty: bool) -> Self {
+        self.remove_empty = remove_empty;
+        self
+    }
+}
+
+impl<'a, T, A: Argument<OsString> + ?Sized> Argument<T> for EnvVar<'a, A> {
+    fn append_to<R: ArgumentReceiver<T>>(&self, r: &mut R) {
+        let mut args: Vec<OsString> = Vec::new();
+        self.value.append_to(&mut args);
+        let mut args = args.into_iter();
+        match args.next() {
+            Some(mut value) => {
+                for arg in args {
+                    value.push(" ");
+                    value.push(arg);
+                }
+                r.set_env(OsStr::new(self.name), &value);
+            }
+            None if self.remove_empty => r.remove_env(OsStr::new(self.name)),
+            None => {}
+        }
+    }
+}
+
+/// Values that can be fed to the standard input of a program. Bytes and strings
+/// are written to the program, paths name a file the program reads from.
+pub trait IsStdinPayload {
+    fn to_payload(&self) -> StdinPayload;
+}
+
+impl IsStdinPayload for [u8] {
+    fn to_payload(&self) -> StdinPayload {
+        StdinPayload::Bytes(self.to_vec())
+    }
+}
+
+impl IsStdinPayload for Vec<u8> {
+    fn to_payload(&self) -> StdinPayload {
+        self[..].to_payload()
+    }
+}
+
+impl IsStdinPayload for str {
+    fn to_payload(&self) -> StdinPayload {
+        self.as_bytes().to_payload()
+    }
+}
+
+impl IsStdinPayload for String {
+    fn to_payload(&self) -> StdinPayload {
+        self.as_bytes().to_payload()
+    }
+}
+
+impl IsStdinPayload for Path {
+    fn to_payload(&self) -> StdinPayload {
+        StdinPayload::File(self.to_path_buf())
+    }
+}
+
+impl IsStdinPayload for PathBuf {
+    fn to_payload(&self) -> StdinPayload {
+        self.as_path().to_payload()
+    }
+}
+
+/// A payload fed to the standard input of the program, with [`ArgumentReceiver::set_stdin`]
+pub struct StdinArg<'a, P: ?Sized>(pub &'a P);
+
+impl<'a, T, P: IsStdinPayload + ?Sized> Argument<T> for StdinArg<'a, P> {
+    fn append_to<R: ArgumentReceiver<T>>(&self, r: &mut R) {
+        r.set_stdin(self.0.to_payload());
+    }
+}
+
+/// The versions of the program supporting a field, given as `since` and `until`
+pub struct VersionGate {
+    name: String,
+    since: Option<Version>,
+    until: Option<Version>,
+}
+
+impl VersionGate {
+    pub fn new<N: Into<String>>(name: N, since: Option<&[u64]>, until: Option<&[u64]>) -> Self {
+        VersionGate {
+            name: name.into(),
+            since: since.map(Version::new),
+            until: until.map(Version::new),
+        }
+    }
+
+    /// Checks the target version of a receiver, every field is supported without one
+    pub fn check(self, version: Option<&Version>) -> Result<(), UnsupportedArgument> {
+        match version {
+            Some(version) if !version.is_within(self.since.as_ref(), self.until.as_ref()) => {
+                Err(UnsupportedArgument {
+                    name: self.name,
+                    since: self.since,
+                    until: self.until,
+                    version: version.clone(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A receiver that only records whether anything is passed to it, to find out
+/// whether a field is set
+#[derive(Default)]
+pub struct Probe {
+    received: bool,
+}
+
+impl Probe {
+    pub fn received(&self) -> bool {
+        self.received
+    }
+}
+
+impl<T> ArgumentReceiver<T> for Probe {
+    fn one_arg(&mut self, _arg: T) {
+        self.received = true;
+    }
+
+    fn set_env(&mut self, _key: &OsStr, _value: &OsStr) {
+        self.received = true;
+    }
+
+    fn remove_env(&mut self, _key: &OsStr) {
+        self.received = true;
+    }
+
+    fn set_stdin(&mut self, _payload: StdinPayload) {
+        self.received = true;
+    }
+}
+
+/// A receiver holding back the arguments of a `last` field, to pass them after the
+/// `--` separator only if there are any. Everything else goes to the receiver directly.
+pub struct Separated<'r, ArgType, R> {
+    receiver: &'r mut R,
+    args: Vec<RecordedArgument<ArgType>>,
+}
+
+impl<'r, ArgType, R: ArgumentReceiver<ArgType>> Separated<'r, ArgType, R> {
+    pub fn new(receiver: &'r mut R) -> Self {
+        Separated {
+            receiver,
+            args: Vec::new(),
+        }
+    }
+
+    /// Pass the separator and the held back arguments, or nothing without arguments
+    pub fn finish<S: Argument<ArgType>>(self, separator: S) {
+        if self.args.is_empty() {
+            return;
+        }
+        separator.append_to(self.receiver);
+        for RecordedArgument { arg, untrusted } in self.args {
+            if untrusted {
+                self.receiver.untrusted_arg(arg);
+            } else {
+                self.receiver.one_arg(arg);
+            }
+        }
+    }
+}
+
+impl<'r, ArgType, R: ArgumentReceiver<ArgType>> ArgumentReceiver<ArgType>
+    for Separated<'r, ArgType, R>
+{
+    fn one_arg(&mut self, arg: ArgType) {
+        self.args.push(RecordedArgument {
+            arg,
+            untrusted: false,
+        });
+    }
+
+    fn untrusted_arg(&mut self, arg: ArgType) {
+        self.args.push(RecordedArgument {
+            arg,
+            untrusted: true,
+        });
+    }
+
+    fn set_env(&mut self, key: &OsStr, value: &OsStr) {
+        self.receiver.set_env(key, value);
+    }
+
+    fn remove_env(&mut self, key: &OsStr) {
+        self.receiver.remove_env(key);
+    }
+
+    fn set_stdin(&mut self, payload: StdinPayload) {
+        self.receiver.set_stdin(payload);
+    }
+
+    fn target_version(&self) -> Option<&Version> {
+        self.receiver.target_version()
+    }
+
+    fn unsupported_arg(&mut self, unsupported: UnsupportedArgument) {
+        self.receiver.unsupported_arg(unsupported);
+    }
+}
+
+/// Values that can be checked by the `non_empty` constraint
+pub trait IsEmptyValue {
+    fn is_empty_value(&self) -> bool;
+}
+
+impl<T: IsEmptyValue + ?Sized> IsEmptyValue for &T {
+    fn is_empty_value(&self) -> bool {
+        (**self).is_empty_value()
+    }
+}
+
+impl IsEmptyValue for OsStr {
+    fn is_empty_value(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl IsEmptyValue for Path {
+    fn is_empty_value(&self) -> bool {
+        self.as_os_str().is_empty()
+    }
 }
 
-impl<'a, A: Argument<OsString>> Argument<OsString> for Named<'a, A> {
-    fn append_to<R: ArgumentReceiver<OsString>>(&self, r: &mut R) {
-        r.arg(self.dashed_flag_name);
-        self.arg.append_to(r);
+impl IsEmptyValue for PathBuf {
+    fn is_empty_value(&self) -> bool {
+        self.as_os_str().is_empty()
     }
 }
 
+macro_rules! is_empty_value {
+    ($( [$($params:tt)*] $ty:ty ),* $(,)?) => {
+        $(
+            impl<$($params)*> IsEmptyValue for $ty {
+                fn is_empty_value(&self) -> bool {
+                    self.is_empty()
+                }
+            }
+        )*
+    };
+}
+
+is_empty_value!(
+    [] str,
+    [] String,
+    [] OsString,
+    [T] [T],
+    [T] Vec<T>,
+    [T] VecDeque<T>,
+    [T] BTreeSet<T>,
+    [T, S] HashSet<T, S>,
+    [K, V] BTreeMap<K, V>,
+    [K, V, S] HashMap<K, V, S>,
+);
+
+/// The `regex` constraint compiles its pattern with this crate
+#[cfg(feature = "regex")]
+pub use regex;
+
 /// A single conditional argument to a command
 pub trait IsArgumentFlag {
     fn is_set(&self) -> bool;
 }
 
-pub struct FlagArg {
-    dashed_flag_name: &'static str,
+pub struct FlagArg<'a> {
+    dashed_flag_name: Cow<'a, str>,
     is_set: bool,
 }
 
-impl FlagArg {
-    pub fn new<A: IsArgumentFlag>(dashed_flag_name: &'static str, arg: &A) -> FlagArg {
+impl<'a> FlagArg<'a> {
+    pub fn new<N: Into<Cow<'a, str>>, A: IsArgumentFlag>(
+        dashed_flag_name: N,
+        arg: &A,
+    ) -> FlagArg<'a> {
         FlagArg {
-            dashed_flag_name,
+            dashed_flag_name: dashed_flag_name.into(),
             is_set: arg.is_set(),
         }
     }
 }
 
-impl Argument<OsString> for FlagArg {
-    fn append_to<R: ArgumentReceiver<OsString>>(&self, r: &mut R) {
+impl<'a, T: ArgumentType> Argument<T> for FlagArg<'a> {
+    fn append_to<R: ArgumentReceiver<T>>(&self, r: &mut R) {
         if self.is_set {
-            r.arg(self.dashed_flag_name);
+            r.one_arg(T::from_text(&self.dashed_flag_name));
+        }
+    }
+}
+
+/// A flag that can be explicitly set, explicitly unset or left at the program's default
+pub trait IsNegatableFlag {
+    /// `Some(true)` for the flag, `Some(false)` for its negation and `None` for neither
+    fn flag_state(&self) -> Option<bool>;
+}
+
+impl<F: IsArgumentFlag> IsNegatableFlag for Option<F> {
+    fn flag_state(&self) -> Option<bool> {
+        self.as_ref().map(IsArgumentFlag::is_set)
+    }
+}
+
+// --flagname, --no-flagname or nothing
+pub struct NegatableFlagArg<'a> {
+    dashed_flag_name: Cow<'a, str>,
+    dashed_negative_name: Cow<'a, str>,
+    state: Option<bool>,
+}
+
+impl<'a> NegatableFlagArg<'a> {
+    pub fn new<N, M, A>(
+        dashed_flag_name: N,
+        dashed_negative_name: M,
+        arg: &A,
+    ) -> NegatableFlagArg<'a>
+    where
+        N: Into<Cow<'a, str>>,
+        M: Into<Cow<'a, str>>,
+        A: IsNegatableFlag,
+    {
+        NegatableFlagArg {
+            dashed_flag_name: dashed_flag_name.into(),
+            dashed_negative_name: dashed_negative_name.into(),
+            state: arg.flag_state(),
+        }
+    }
+}
+
+impl<'a, T: ArgumentType> Argument<T> for NegatableFlagArg<'a> {
+    fn append_to<R: ArgumentReceiver<T>>(&self, r: &mut R) {
+        match self.state {
+            Some(true) => {
+                r.one_arg(T::from_text(&self.dashed_flag_name));
+            }
+            Some(false) => {
+                r.one_arg(T::from_text(&self.dashed_negative_name));
+            }
+            None => {}
+        }
+    }
+}
+
+/// Integers that are passed as a repeated flag
+pub trait IsArgumentCount {
+    /// How often the flag is repeated. Negative counts repeat the negative flag instead.
+    fn count(&self) -> i64;
+}
+
+macro_rules! lossless_count {
+    ($($ty:ty),*) => {
+        $(
+            impl IsArgumentCount for $ty {
+                fn count(&self) -> i64 {
+                    i64::from(*self)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! saturating_count {
+    ($($ty:ty),*) => {
+        $(
+            impl IsArgumentCount for $ty {
+                #[allow(unused_comparisons)]
+                fn count(&self) -> i64 {
+                    use std::convert::TryFrom;
+                    i64::try_from(*self).unwrap_or(if *self > 0 { i64::MAX } else { i64::MIN })
+                }
+            }
+        )*
+    };
+}
+
+lossless_count!(u8, u16, u32, i8, i16, i32, i64);
+saturating_count!(u64, usize, isize);
+
+// -v -v -v, -vvv, -q or nothing
+pub struct CountArg<'a> {
+    dashed_flag_name: Cow<'a, str>,
+    dashed_negative_name: Option<Cow<'a, str>>,
+    bundle: bool,
+    count: i64,
+}
+
+impl<'a> CountArg<'a> {
+    pub fn new<N: Into<Cow<'a, str>>, A: IsArgumentCount>(dashed_flag_name: N, arg: &A) -> Self {
+        CountArg {
+            dashed_flag_name: dashed_flag_name.into(),
+            dashed_negative_name: None,
+            bundle: false,
+            count: arg.count(),
+        }
+    }
+
+    /// Repeat `dashed_negative_name` for negative counts. Without it, negative
+    /// counts pass nothing.
+    pub fn with_negative<N: Into<Cow<'a, str>>>(mut self, dashed_negative_name: N) -> Self {
+        self.dashed_negative_name = Some(dashed_negative_name.into());
+        self
+    }
+
+    /// Bundle the repetitions of a short flag into a single argument, e.g. `-vvv`.
+    /// Long flags are always repeated as separate arguments.
+    pub fn bundled(mut self, bundle: bool) -> Self {
+        self.bundle = bundle;
+        self
+    }
+}
+
+/// The flag character of a short flag such as `-v`
+fn short_flag_char(dashed_flag_name: &str) -> Option<char> {
+    let mut chars = dashed_flag_name.strip_prefix('-')?.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c != '-' => Some(c),
+        _ => None,
+    }
+}
+
+impl<'a, T: ArgumentType> Argument<T> for CountArg<'a> {
+    fn append_to<R: ArgumentReceiver<T>>(&self, r: &mut R) {
+        let name = match &self.dashed_negative_name {
+            _ if self.count >= 0 => &self.dashed_flag_name,
+            Some(negative) => negative,
+            None => return,
+        };
+        let count = self.count.unsigned_abs();
+        match short_flag_char(name) {
+            Some(c) if self.bundle && count > 0 => {
+                r.one_arg(T::from_text(&format!(
+                    "-{}",
+                    c.to_string().repeat(count as usize)
+                )));
+            }
+            _ => {
+                for _ in 0..count {
+                    r.one_arg(T::from_text(name));
+                }
+            }
+        }
+    }
+}
+
+/// Arguments that can be flattened into another argument, such as derived specs.
+/// The derived flag names of a flattened argument can be namespaced with a prefix.
+pub trait FlattenArgument<ArgType = OsString>: Argument<ArgType> {
+    /// Append the argument to the command, inserting `prefix` into each derived
+    /// flag name after its leading dashes.
+    fn append_prefixed<R: ArgumentReceiver<ArgType>>(&self, prefix: &str, r: &mut R);
+}
+
+impl<ArgType, A: FlattenArgument<ArgType>> FlattenArgument<ArgType> for Option<A> {
+    fn append_prefixed<R: ArgumentReceiver<ArgType>>(&self, prefix: &str, r: &mut R) {
+        if let Some(arg) = self {
+            arg.append_prefixed(prefix, r);
         }
     }
 }
 
-#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Default)]
 pub enum Flag {
+    #[default]
     Unset,
     Set,
 }
 
-impl Default for Flag {
-    fn default() -> Self {
-        Flag::Unset
+impl IsArgumentFlag for Flag {
+    fn is_set(&self) -> bool {
+        *self == Flag::Set
     }
 }
 
-impl IsArgumentFlag for Flag {
+impl IsArgumentFlag for bool {
     fn is_set(&self) -> bool {
-        *self == Flag::Set
+        *self
+    }
+}
+
+/// Enums whose unit variants each stand for one value of an option, such as
+/// `--color=always|never|auto`. Usually derived with `#[derive(ArgumentValue)]`.
+pub trait ArgumentValue: Sized + 'static {
+    /// All possible values, in declaration order
+    const VALUES: &'static [&'static str];
+    /// The value as passed to the program
+    fn as_value(&self) -> &'static str;
+}
+
+/// The error when parsing a string that is not one of the [`ArgumentValue::VALUES`].
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct ParseValueError {
+    value: String,
+    expected: &'static [&'static str],
+}
+
+impl ParseValueError {
+    pub fn new(value: &str, expected: &'static [&'static str]) -> ParseValueError {
+        ParseValueError {
+            value: value.to_owned(),
+            expected,
+        }
+    }
+}
+
+impl std::fmt::Display for ParseValueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown value `{}`, expected one of ", self.value)?;
+        for (idx, expected) in self.expected.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "`{}`", expected)?;
+        }
+        Ok(())
     }
 }
+
+impl std::error::Error for ParseValueError {}
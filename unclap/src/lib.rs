@@ -1,4 +1,8 @@
 pub use unclap_core::*;
 pub use unclap_derive::*;
 
-pub use unclap_support::Flag;
+pub use unclap_support::{ArgumentValue, Flag, KeyValuePairs, ParseValueError, SingleArgument};
+
+/// The building blocks of derived implementations, which refer to them as
+/// `unclap::support`, or as `support` in the path given by `#[argument(crate = "...")]`.
+pub use unclap_support as support;
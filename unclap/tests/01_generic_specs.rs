@@ -0,0 +1,79 @@
+mod common;
+
+use common::args_of;
+use std::ffi::OsStr;
+use std::path::Path;
+use unclap::{Argument, Flag};
+
+#[derive(Argument)]
+struct CpSpec<'a> {
+    #[argument(variant(flag = "-r"))]
+    recursive: Flag,
+    #[argument(variant(named = "-t"))]
+    target_directory: &'a Path,
+    sources: Vec<&'a str>,
+}
+
+#[derive(Argument)]
+struct Wrap<T> {
+    #[argument(variant(named))]
+    inner: T,
+}
+
+#[derive(Argument)]
+struct Toggle<F> {
+    #[argument(variant(flag))]
+    verbose: F,
+}
+
+#[derive(Argument)]
+enum Either<'a, L, R: Argument> {
+    Left(L),
+    Right(R),
+    #[argument(variant(named))]
+    Neither,
+    Borrowed(&'a OsStr),
+}
+
+#[test]
+fn test_borrowed_spec() {
+    let sources = [String::from("a.txt"), String::from("b.txt")];
+    let target = Path::new("/tmp/target");
+    let spec = CpSpec {
+        recursive: Flag::Set,
+        target_directory: target,
+        sources: sources.iter().map(String::as_str).collect(),
+    };
+    assert_eq!(
+        args_of(&spec),
+        ["-r", "-t", "/tmp/target", "a.txt", "b.txt"]
+    );
+}
+
+#[test]
+fn test_generic_spec() {
+    let spec = Wrap {
+        inner: String::from("value"),
+    };
+    assert_eq!(args_of(&spec), ["--inner", "value"]);
+
+    let nested = Wrap {
+        inner: Wrap { inner: "deep" },
+    };
+    assert_eq!(args_of(&nested), ["--inner", "--inner", "deep"]);
+
+    let toggle = Toggle { verbose: Flag::Set };
+    assert_eq!(args_of(&toggle), ["--verbose"]);
+}
+
+#[test]
+fn test_generic_enum() {
+    let left: Either<String, &str> = Either::Left(String::from("left"));
+    assert_eq!(args_of(&left), ["left"]);
+    let right: Either<String, &str> = Either::Right("right");
+    assert_eq!(args_of(&right), ["right"]);
+    let neither: Either<String, &str> = Either::Neither;
+    assert_eq!(args_of(&neither), ["--neither"]);
+    let borrowed: Either<String, &str> = Either::Borrowed(OsStr::new("os"));
+    assert_eq!(args_of(&borrowed), ["os"]);
+}
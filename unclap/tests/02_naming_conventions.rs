@@ -0,0 +1,77 @@
+mod common;
+
+use common::args_of;
+use unclap::{Argument, Flag};
+
+#[derive(Argument)]
+#[argument(prefix = "-")]
+struct FfmpegSpec {
+    #[argument(variant(named))]
+    preset: String,
+    #[argument(variant(named = "-c:v"))]
+    video_codec: String,
+    #[argument(variant(flag))]
+    no_stdin: Flag,
+}
+
+#[derive(Argument)]
+#[argument(rename_all = "snake_case")]
+struct NodeSpec {
+    #[argument(variant(named))]
+    max_old_space_size: String,
+    #[argument(variant(flag = "--inspect"))]
+    inspect_brk: Flag,
+}
+
+#[derive(Argument)]
+#[argument(rename_all = "PascalCase", prefix = "/")]
+struct RobocopySpec {
+    #[argument(variant(flag))]
+    mirror_all: Flag,
+}
+
+#[derive(Argument)]
+#[argument(rename_all = "camelCase")]
+#[allow(dead_code)]
+enum Mode {
+    #[argument(variant(named))]
+    ShowVersion,
+    #[argument(variant(named = "-h"))]
+    Help,
+}
+
+#[test]
+fn test_single_dash_prefix() {
+    let spec = FfmpegSpec {
+        preset: String::from("veryfast"),
+        video_codec: String::from("libx264"),
+        no_stdin: Flag::Set,
+    };
+    assert_eq!(
+        args_of(&spec),
+        ["-preset", "veryfast", "-c:v", "libx264", "-no-stdin"]
+    );
+}
+
+#[test]
+fn test_rename_all() {
+    let spec = NodeSpec {
+        max_old_space_size: String::from("4096"),
+        inspect_brk: Flag::Set,
+    };
+    assert_eq!(
+        args_of(&spec),
+        ["--max_old_space_size", "4096", "--inspect"]
+    );
+
+    let spec = RobocopySpec {
+        mirror_all: Flag::Set,
+    };
+    assert_eq!(args_of(&spec), ["/MirrorAll"]);
+}
+
+#[test]
+fn test_rename_all_variants() {
+    assert_eq!(args_of(&Mode::ShowVersion), ["--showVersion"]);
+    assert_eq!(args_of(&Mode::Help), ["-h"]);
+}
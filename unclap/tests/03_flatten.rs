@@ -0,0 +1,109 @@
+mod common;
+
+use common::args_of;
+use unclap::{Argument, Flag};
+
+#[derive(Argument)]
+struct DbSpec {
+    #[argument(variant(named))]
+    host: String,
+    #[argument(variant(named))]
+    port: String,
+    #[argument(variant(flag = "--no-db"))]
+    disabled: Flag,
+}
+
+#[derive(Argument)]
+#[argument(prefix = "-")]
+enum LogLevel {
+    #[argument(variant(named))]
+    Quiet,
+    #[argument(variant(named))]
+    Verbose,
+}
+
+#[derive(Argument)]
+struct ServerSpec {
+    #[argument(flatten(prefix = "db-"))]
+    primary: DbSpec,
+    #[argument(flatten(prefix = "replica-"))]
+    replica: DbSpec,
+    #[argument(flatten(prefix = "log-"))]
+    log_level: LogLevel,
+    #[argument(flatten)]
+    cache: DbSpec,
+}
+
+#[derive(Argument)]
+struct ClusterSpec {
+    #[argument(flatten(prefix = "cluster-"))]
+    server: ServerSpec,
+}
+
+#[test]
+fn test_flatten_prefix() {
+    let spec = ServerSpec {
+        primary: DbSpec {
+            host: String::from("db.local"),
+            port: String::from("5432"),
+            disabled: Flag::Unset,
+        },
+        replica: DbSpec {
+            host: String::from("replica.local"),
+            port: String::from("5433"),
+            disabled: Flag::Set,
+        },
+        log_level: LogLevel::Verbose,
+        cache: DbSpec {
+            host: String::from("cache.local"),
+            port: String::from("6379"),
+            disabled: Flag::Unset,
+        },
+    };
+    assert_eq!(
+        args_of(&spec),
+        [
+            "--db-host",
+            "db.local",
+            "--db-port",
+            "5432",
+            "--replica-host",
+            "replica.local",
+            "--replica-port",
+            "5433",
+            "--no-db",
+            "-log-verbose",
+            "--host",
+            "cache.local",
+            "--port",
+            "6379",
+        ]
+    );
+}
+
+#[test]
+fn test_nested_flatten_prefix() {
+    let spec = ClusterSpec {
+        server: ServerSpec {
+            primary: DbSpec {
+                host: String::from("db.local"),
+                port: String::from("5432"),
+                disabled: Flag::Unset,
+            },
+            replica: DbSpec {
+                host: String::from("replica.local"),
+                port: String::from("5433"),
+                disabled: Flag::Unset,
+            },
+            log_level: LogLevel::Quiet,
+            cache: DbSpec {
+                host: String::from("cache.local"),
+                port: String::from("6379"),
+                disabled: Flag::Unset,
+            },
+        },
+    };
+    let args = args_of(&spec);
+    assert_eq!(args[..2], ["--cluster-db-host", "db.local"]);
+    assert_eq!(args[8..10], ["-cluster-log-quiet", "--cluster-host"]);
+}
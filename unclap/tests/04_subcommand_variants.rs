@@ -0,0 +1,72 @@
+mod common;
+
+use common::args_of;
+use std::path::PathBuf;
+use unclap::{Argument, Flag};
+
+#[derive(Argument)]
+#[allow(dead_code)]
+enum GitCommand {
+    Commit {
+        #[argument(variant(named))]
+        message: String,
+        #[argument(variant(flag))]
+        amend: Flag,
+    },
+    #[argument(rename = "ls-files")]
+    ListFiles {
+        #[argument(variant(flag = "-z"))]
+        null_terminated: Flag,
+        paths: Vec<PathBuf>,
+    },
+    #[argument(variant(subcommand))]
+    Status,
+    #[argument(variant(unnamed))]
+    Raw { args: Vec<String> },
+}
+
+#[derive(Argument)]
+#[argument(rename_all = "snake_case", prefix = "-")]
+#[allow(dead_code)]
+enum Custom {
+    ShowAll {
+        #[argument(variant(named))]
+        max_depth: String,
+    },
+    #[argument(variant(named), rename = "v")]
+    Version,
+}
+
+#[test]
+fn test_struct_variant_subcommand() {
+    let commit = GitCommand::Commit {
+        message: String::from("Initial commit"),
+        amend: Flag::Set,
+    };
+    assert_eq!(
+        args_of(&commit),
+        ["commit", "--message", "Initial commit", "--amend"]
+    );
+
+    let ls_files = GitCommand::ListFiles {
+        null_terminated: Flag::Set,
+        paths: vec![PathBuf::from("src")],
+    };
+    assert_eq!(args_of(&ls_files), ["ls-files", "-z", "src"]);
+
+    assert_eq!(args_of(&GitCommand::Status), ["status"]);
+
+    let raw = GitCommand::Raw {
+        args: vec![String::from("gc"), String::from("--aggressive")],
+    };
+    assert_eq!(args_of(&raw), ["gc", "--aggressive"]);
+}
+
+#[test]
+fn test_variant_naming() {
+    let show_all = Custom::ShowAll {
+        max_depth: String::from("3"),
+    };
+    assert_eq!(args_of(&show_all), ["show_all", "-max_depth", "3"]);
+    assert_eq!(args_of(&Custom::Version), ["-v"]);
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use common::args_of;
+use std::path::PathBuf;
+use unclap::{Argument, Flag};
+
+#[derive(Argument)]
+#[allow(dead_code)]
+enum Operation {
+    #[argument(variant(subcommand))]
+    Move(PathBuf, PathBuf),
+    #[argument(variant(named))]
+    Resize(u32, u32),
+    #[argument(variant(subcommand), rename = "cp")]
+    Copy(
+        #[argument(variant(flag = "-r"))] Flag,
+        #[argument(variant(named = "-t"))] PathBuf,
+        Vec<PathBuf>,
+    ),
+}
+
+#[test]
+fn test_multi_field_tuple_variants() {
+    let mv = Operation::Move(PathBuf::from("a"), PathBuf::from("b"));
+    assert_eq!(args_of(&mv), ["move", "a", "b"]);
+
+    let resize = Operation::Resize(1920, 1080);
+    assert_eq!(args_of(&resize), ["--resize", "1920", "1080"]);
+
+    let copy = Operation::Copy(
+        Flag::Set,
+        PathBuf::from("dest"),
+        vec![PathBuf::from("x"), PathBuf::from("y")],
+    );
+    assert_eq!(args_of(&copy), ["cp", "-r", "-t", "dest", "x", "y"]);
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use common::args_of;
+use unclap::{Argument, ArgumentValue, ParseValueError};
+
+#[derive(ArgumentValue, Debug, PartialEq)]
+enum ColorChoice {
+    Always,
+    Never,
+    Auto,
+}
+
+#[derive(ArgumentValue, Debug, PartialEq)]
+#[argument(rename_all = "lowercase")]
+enum Preset {
+    UltraFast,
+    VeryFast,
+    #[argument(rename = "slow")]
+    Placebo,
+}
+
+#[derive(Argument)]
+struct LsSpec {
+    #[argument(variant(named))]
+    color: ColorChoice,
+    #[argument(variant(named = "-preset"))]
+    preset: Preset,
+    positional: ColorChoice,
+}
+
+#[test]
+fn test_value_enum_as_option_value() {
+    let spec = LsSpec {
+        color: ColorChoice::Never,
+        preset: Preset::VeryFast,
+        positional: ColorChoice::Auto,
+    };
+    assert_eq!(
+        args_of(&spec),
+        ["--color", "never", "-preset", "veryfast", "auto"]
+    );
+}
+
+#[test]
+fn test_value_enum_display_from_str() {
+    assert_eq!(ColorChoice::VALUES, ["always", "never", "auto"]);
+    assert_eq!(Preset::VALUES, ["ultrafast", "veryfast", "slow"]);
+    assert_eq!(Preset::Placebo.to_string(), "slow");
+    assert_eq!("always".parse(), Ok(ColorChoice::Always));
+    assert_eq!("ultrafast".parse(), Ok(Preset::UltraFast));
+
+    let err = "placebo".parse::<Preset>().unwrap_err();
+    assert_eq!(
+        err,
+        ParseValueError::new("placebo", &["ultrafast", "veryfast", "slow"])
+    );
+    assert_eq!(
+        err.to_string(),
+        "unknown value `placebo`, expected one of `ultrafast`, `veryfast`, `slow`"
+    );
+}
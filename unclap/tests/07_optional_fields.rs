@@ -0,0 +1,75 @@
+mod common;
+
+use common::args_of;
+use std::path::PathBuf;
+use unclap::Argument;
+
+#[derive(Argument)]
+struct CompileSpec {
+    #[argument(variant(named = "-o"))]
+    output: Option<PathBuf>,
+    #[argument(variant(named))]
+    target: std::option::Option<String>,
+    #[argument(flatten(prefix = "linker-"))]
+    linker: Option<LinkerSpec>,
+    input: Option<PathBuf>,
+    extra: Vec<String>,
+}
+
+#[derive(Argument)]
+struct LinkerSpec {
+    #[argument(variant(named))]
+    path: Option<PathBuf>,
+}
+
+#[derive(Argument)]
+#[allow(dead_code)]
+enum Action {
+    Build {
+        #[argument(variant(named))]
+        jobs: Option<u32>,
+    },
+}
+
+#[test]
+fn test_absent_options_are_omitted() {
+    let spec = CompileSpec {
+        output: None,
+        target: None,
+        linker: None,
+        input: None,
+        extra: vec![String::from("-g")],
+    };
+    assert_eq!(args_of(&spec), ["-g"]);
+
+    let build = Action::Build { jobs: None };
+    assert_eq!(args_of(&build), ["build"]);
+}
+
+#[test]
+fn test_present_options() {
+    let spec = CompileSpec {
+        output: Some(PathBuf::from("a.out")),
+        target: Some(String::from("x86_64")),
+        linker: Some(LinkerSpec {
+            path: Some(PathBuf::from("/usr/bin/ld")),
+        }),
+        input: Some(PathBuf::from("main.c")),
+        extra: vec![],
+    };
+    assert_eq!(
+        args_of(&spec),
+        [
+            "-o",
+            "a.out",
+            "--target",
+            "x86_64",
+            "--linker-path",
+            "/usr/bin/ld",
+            "main.c"
+        ]
+    );
+
+    let build = Action::Build { jobs: Some(4) };
+    assert_eq!(args_of(&build), ["build", "--jobs", "4"]);
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use common::args_of;
+use unclap::{Argument, Flag};
+
+#[derive(Argument)]
+struct GitLogSpec {
+    #[argument(variant(flag))]
+    oneline: bool,
+    #[argument(variant(flag = "-p"))]
+    patch: bool,
+    #[argument(variant(flag(negatable)))]
+    decorate: Option<bool>,
+    #[argument(variant(flag(name = "--color", negatable)))]
+    colorize: Option<Flag>,
+    #[argument(variant(flag(negatable, negative = "--no-ext-diff")))]
+    ext_diff: Option<bool>,
+}
+
+#[derive(Argument)]
+#[argument(rename_all = "camelCase", prefix = "-")]
+struct CamelSpec {
+    #[argument(variant(flag(negatable)))]
+    cache_results: Option<bool>,
+}
+
+#[derive(Argument)]
+struct Parent {
+    #[argument(flatten(prefix = "git-"))]
+    log: GitLogSpec,
+}
+
+#[test]
+fn test_bool_flags() {
+    let spec = GitLogSpec {
+        oneline: true,
+        patch: false,
+        decorate: None,
+        colorize: None,
+        ext_diff: None,
+    };
+    assert_eq!(args_of(&spec), ["--oneline"]);
+}
+
+#[test]
+fn test_negatable_flags() {
+    let spec = GitLogSpec {
+        oneline: true,
+        patch: false,
+        decorate: Some(true),
+        colorize: Some(Flag::Set),
+        ext_diff: Some(true),
+    };
+    assert_eq!(
+        args_of(&spec),
+        ["--oneline", "--decorate", "--color", "--ext-diff"]
+    );
+    let spec = GitLogSpec {
+        decorate: Some(false),
+        colorize: Some(Flag::Unset),
+        ext_diff: Some(false),
+        ..spec
+    };
+    assert_eq!(
+        args_of(&spec),
+        ["--oneline", "--no-decorate", "--no-color", "--no-ext-diff"]
+    );
+    assert_eq!(
+        args_of(&CamelSpec {
+            cache_results: Some(false)
+        }),
+        ["-noCacheResults"]
+    );
+    assert_eq!(
+        args_of(&Parent { log: spec }),
+        [
+            "--git-oneline",
+            "--no-git-decorate",
+            "--no-color",
+            "--no-ext-diff"
+        ]
+    );
+}
+
+#[derive(Argument)]
+struct Client {
+    #[argument(variant(flag(negatable)))]
+    color: Option<bool>,
+}
+
+#[derive(Argument)]
+struct Psql {
+    #[argument(flatten(prefix = "db-"))]
+    client: Client,
+}
+
+#[test]
+fn test_prefixed_negatable_flags() {
+    let psql = Psql {
+        client: Client { color: Some(false) },
+    };
+    assert_eq!(args_of(&psql), ["--no-db-color"]);
+    let psql = Psql {
+        client: Client { color: Some(true) },
+    };
+    assert_eq!(args_of(&psql), ["--db-color"]);
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use common::args_of;
+use unclap::Argument;
+
+#[derive(Argument)]
+struct SshSpec {
+    #[argument(variant(count(name = "-v", bundle, negative = "-q")))]
+    verbosity: i8,
+    #[argument(variant(count))]
+    debug: u32,
+    #[argument(variant(count = "-d"))]
+    detach: usize,
+}
+
+#[test]
+fn test_counted_flags() {
+    let spec = SshSpec {
+        verbosity: 0,
+        debug: 0,
+        detach: 0,
+    };
+    assert_eq!(args_of(&spec), Vec::<String>::new());
+    let spec = SshSpec {
+        verbosity: 3,
+        ..spec
+    };
+    assert_eq!(args_of(&spec), ["-vvv"]);
+    let spec = SshSpec {
+        verbosity: -2,
+        ..spec
+    };
+    assert_eq!(args_of(&spec), ["-qq"]);
+    let spec = SshSpec {
+        verbosity: 0,
+        debug: 2,
+        ..spec
+    };
+    assert_eq!(args_of(&spec), ["--debug", "--debug"]);
+    let spec = SshSpec {
+        verbosity: 1,
+        debug: 1,
+        detach: 2,
+    };
+    assert_eq!(args_of(&spec), ["-v", "--debug", "-d", "-d"]);
+}
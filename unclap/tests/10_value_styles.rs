@@ -0,0 +1,99 @@
+mod common;
+
+use common::args_of;
+use std::path::PathBuf;
+use unclap::Argument;
+
+#[derive(Argument)]
+struct CcSpec {
+    #[argument(variant(named(name = "-O", style = "attached")))]
+    optimization: u8,
+    #[argument(variant(named(name = "-I", style = "attached")))]
+    include: Option<PathBuf>,
+    #[argument(variant(named(style = "equals")))]
+    std: String,
+    #[argument(variant(named(name = "-o")))]
+    output: PathBuf,
+}
+
+#[derive(Argument)]
+#[argument(style = "operand")]
+struct DdSpec {
+    #[argument(variant(named = "if"))]
+    input: PathBuf,
+    #[argument(variant(named = "of"))]
+    output: PathBuf,
+    #[argument(variant(named))]
+    bs: String,
+    #[argument(variant(named(style = "separate")))]
+    status: Option<String>,
+}
+
+#[derive(Argument)]
+#[argument(style = "equals")]
+struct LsSpec {
+    #[argument(variant(named))]
+    color: String,
+}
+
+#[test]
+fn test_attached_and_equals() {
+    let spec = CcSpec {
+        optimization: 2,
+        include: Some(PathBuf::from("/usr/include")),
+        std: String::from("c11"),
+        output: PathBuf::from("a.out"),
+    };
+    assert_eq!(
+        args_of(&spec),
+        ["-O2", "-I/usr/include", "--std=c11", "-o", "a.out"]
+    );
+}
+
+#[test]
+fn test_container_style() {
+    let spec = DdSpec {
+        input: PathBuf::from("/dev/zero"),
+        output: PathBuf::from("disk.img"),
+        bs: String::from("1M"),
+        status: Some(String::from("progress")),
+    };
+    assert_eq!(
+        args_of(&spec),
+        [
+            "if=/dev/zero",
+            "of=disk.img",
+            "bs=1M",
+            "--status",
+            "progress"
+        ]
+    );
+
+    let spec = LsSpec {
+        color: String::from("auto"),
+    };
+    assert_eq!(args_of(&spec), ["--color=auto"]);
+}
+
+#[derive(Argument)]
+struct GoTestSpec {
+    #[argument(variant(named(style = "equals")))]
+    tags: Vec<String>,
+    #[argument(variant(named(name = "-I", style = "attached")))]
+    include: Vec<PathBuf>,
+}
+
+#[test]
+fn test_joined_collections() {
+    let spec = GoTestSpec {
+        tags: vec![String::from("unit"), String::from("race")],
+        include: vec![PathBuf::from("src")],
+    };
+    assert_eq!(args_of(&spec), ["--tags=unit", "--tags=race", "-Isrc"]);
+
+    let spec = GoTestSpec {
+        tags: Vec::new(),
+        include: Vec::new(),
+    };
+    assert_eq!(args_of(&spec), Vec::<String>::new());
+}
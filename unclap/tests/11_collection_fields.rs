@@ -0,0 +1,87 @@
+mod common;
+
+use common::args_of;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use unclap::Argument;
+
+#[derive(Argument)]
+struct CcSpec {
+    #[argument(variant(named(name = "-I", multiple = "repeat", style = "attached")))]
+    include_dirs: Vec<PathBuf>,
+    #[argument(variant(named(name = "-D", multiple = "repeat")))]
+    defines: BTreeSet<String>,
+    inputs: Vec<PathBuf>,
+}
+
+#[derive(Argument)]
+struct CargoSpec {
+    #[argument(variant(named(multiple(delimited = ","))))]
+    features: Vec<String>,
+    #[argument(variant(named(name = "-cp", multiple(delimited(":")))))]
+    class_path: Option<Vec<PathBuf>>,
+    #[argument(variant(named(multiple = "once")))]
+    include: Vec<String>,
+}
+
+#[derive(Argument)]
+struct GenericSpec<T> {
+    #[argument(variant(named(multiple = "repeat", style = "equals")))]
+    values: Vec<T>,
+}
+
+#[test]
+fn test_repeated_option() {
+    let spec = CcSpec {
+        include_dirs: vec![PathBuf::from("a"), PathBuf::from("b")],
+        defines: ["NDEBUG", "FOO"].iter().map(|s| s.to_string()).collect(),
+        inputs: vec![PathBuf::from("main.c")],
+    };
+    assert_eq!(
+        args_of(&spec),
+        ["-Ia", "-Ib", "-D", "FOO", "-D", "NDEBUG", "main.c"]
+    );
+
+    let generic = GenericSpec {
+        values: vec![1u8, 2],
+    };
+    assert_eq!(args_of(&generic), ["--values=1", "--values=2"]);
+}
+
+#[test]
+fn test_delimited_and_once() {
+    let spec = CargoSpec {
+        features: vec![String::from("a"), String::from("b"), String::from("c")],
+        class_path: Some(vec![PathBuf::from("a.jar"), PathBuf::from("b.jar")]),
+        include: vec![String::from("x"), String::from("y")],
+    };
+    assert_eq!(
+        args_of(&spec),
+        [
+            "--features",
+            "a,b,c",
+            "-cp",
+            "a.jar:b.jar",
+            "--include",
+            "x",
+            "y"
+        ]
+    );
+}
+
+#[test]
+fn test_empty_collections_are_omitted() {
+    let spec = CcSpec {
+        include_dirs: vec![],
+        defines: BTreeSet::new(),
+        inputs: vec![],
+    };
+    assert_eq!(args_of(&spec), Vec::<String>::new());
+
+    let spec = CargoSpec {
+        features: vec![],
+        class_path: Some(vec![]),
+        include: vec![],
+    };
+    assert_eq!(args_of(&spec), Vec::<String>::new());
+}
@@ -0,0 +1,123 @@
+mod common;
+
+use common::args_of;
+use std::collections::{BTreeMap, HashMap};
+use unclap::{Argument, ArgumentExt};
+
+#[derive(Argument)]
+struct JavaSpec {
+    #[argument(variant(named(
+        name = "-D",
+        key_value = "=",
+        multiple = "repeat",
+        style = "attached"
+    )))]
+    properties: BTreeMap<String, String>,
+}
+
+#[derive(Argument)]
+struct CcSpec {
+    #[argument(variant(named(
+        name = "-D",
+        key_value = "=",
+        multiple = "repeat",
+        style = "attached"
+    )))]
+    defines: BTreeMap<String, Option<String>>,
+}
+
+#[derive(Argument)]
+struct DockerRunSpec {
+    #[argument(variant(named(name = "-e", key_value = "=", multiple = "repeat")))]
+    env: HashMap<String, String>,
+    #[argument(variant(named(name = "--label", key_value = "=", multiple(delimited = ","))))]
+    labels: Option<Vec<(String, u32)>>,
+    image: String,
+}
+
+#[derive(Argument)]
+struct HelmSpec<K: Ord, V> {
+    #[argument(variant(named(key_value = "=")))]
+    set: HashMap<K, V>,
+}
+
+fn strings(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_repeated_key_values() {
+    let spec = JavaSpec {
+        properties: strings(&[("user.home", "/tmp"), ("file.encoding", "UTF-8")])
+            .into_iter()
+            .collect(),
+    };
+    assert_eq!(
+        args_of(&spec),
+        ["-Dfile.encoding=UTF-8", "-Duser.home=/tmp"]
+    );
+}
+
+#[test]
+fn test_key_without_value() {
+    let spec = CcSpec {
+        defines: vec![
+            (String::from("NDEBUG"), None),
+            (String::from("VERSION"), Some(String::from("2"))),
+        ]
+        .into_iter()
+        .collect(),
+    };
+    assert_eq!(args_of(&spec), ["-DNDEBUG", "-DVERSION=2"]);
+}
+
+#[test]
+fn test_hash_map_order_is_deterministic() {
+    let spec = DockerRunSpec {
+        env: strings(&[
+            ("PATH", "/bin"),
+            ("HOME", "/root"),
+            ("TERM", "xterm"),
+            ("A", ""),
+        ])
+        .into_iter()
+        .collect(),
+        labels: Some(vec![(String::from("b"), 2), (String::from("a"), 1)]),
+        image: String::from("alpine"),
+    };
+    assert_eq!(
+        args_of(&spec),
+        [
+            "-e",
+            "A=",
+            "-e",
+            "HOME=/root",
+            "-e",
+            "PATH=/bin",
+            "-e",
+            "TERM=xterm",
+            "--label",
+            "b=2,a=1",
+            "alpine"
+        ]
+    );
+
+    let helm = HelmSpec {
+        set: vec![("b.c", 2), ("a", 1)].into_iter().collect(),
+    };
+    assert_eq!(args_of(&helm), ["--set", "a=1", "b.c=2"]);
+}
+
+#[test]
+fn test_maps_as_plain_arguments() {
+    let map: HashMap<_, _> = vec![("--b", "2"), ("--a", "1")].into_iter().collect();
+    let args: Vec<_> = map
+        .to_command("prog")
+        .get_args()
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(args, ["--a", "1", "--b", "2"]);
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use common::args_of;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::time::Duration;
+use unclap::{Argument, ArgumentReceiver};
+
+pub struct Resolution {
+    w: u32,
+    h: u32,
+}
+
+/// A foreign type without an `Argument` implementation
+pub struct Rgb(u8, u8, u8);
+
+mod render {
+    use super::*;
+
+    pub fn rgb<R: ArgumentReceiver>(color: &Rgb, recv: &mut R) {
+        recv.one_arg(OsString::from(format!(
+            "#{:02x}{:02x}{:02x}",
+            color.0, color.1, color.2
+        )));
+    }
+
+    pub fn search_path<R: ArgumentReceiver>(paths: &[PathBuf], recv: &mut R) {
+        if let Some(joined) = std::env::join_paths(paths).ok().filter(|p| !p.is_empty()) {
+            recv.one_arg(joined);
+        }
+    }
+}
+
+#[derive(Argument)]
+struct FfmpegSpec {
+    #[argument(variant(named = "-s"), format("{}x{}", w, h))]
+    size: Resolution,
+    #[argument(variant(named = "-t"), format("{}s", as_secs()))]
+    duration: Option<Duration>,
+    #[argument(variant(named = "-aspect"), format = "{}:{}")]
+    aspect: (u8, u8),
+    #[argument(variant(named(name = "-r", style = "equals")), format = "{:.2}")]
+    rate: f64,
+    #[argument(variant(named), format = "{w}x{h}")]
+    thumbnail: Resolution,
+    #[argument(variant(named), with = "render::rgb")]
+    background: Rgb,
+    #[argument(variant(named), with = "render::search_path")]
+    path: Vec<PathBuf>,
+    #[argument(with = "render::rgb")]
+    foreground: Option<Rgb>,
+    #[argument(format = "out-{}.mp4")]
+    output: u32,
+}
+
+#[test]
+fn test_custom_rendering() {
+    let spec = FfmpegSpec {
+        size: Resolution { w: 1920, h: 1080 },
+        duration: Some(Duration::from_secs(30)),
+        aspect: (16, 9),
+        rate: 29.97,
+        thumbnail: Resolution { w: 320, h: 180 },
+        background: Rgb(255, 0, 16),
+        path: vec![],
+        foreground: None,
+        output: 3,
+    };
+    assert_eq!(
+        args_of(&spec),
+        [
+            "-s",
+            "1920x1080",
+            "-t",
+            "30s",
+            "-aspect",
+            "16:9",
+            "-r=29.97",
+            "--thumbnail",
+            "320x180",
+            "--background",
+            "#ff0010",
+            "out-3.mp4"
+        ]
+    );
+
+    let spec = FfmpegSpec {
+        duration: None,
+        path: vec![PathBuf::from("/a")],
+        foreground: Some(Rgb(0, 0, 0)),
+        ..spec
+    };
+    let args = args_of(&spec);
+    assert_eq!(args[2..6], ["-aspect", "16:9", "-r=29.97", "--thumbnail"]);
+    assert_eq!(args[9..], ["--path", "/a", "#000000", "out-3.mp4"]);
+}
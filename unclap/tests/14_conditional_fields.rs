@@ -0,0 +1,110 @@
+mod common;
+
+use common::args_of;
+use std::path::PathBuf;
+use unclap::{Argument, ArgumentValue};
+
+#[derive(ArgumentValue, PartialEq, Default)]
+#[allow(dead_code)]
+enum Level {
+    Debug,
+    #[default]
+    Info,
+    Warn,
+}
+
+fn is_empty(s: &str) -> bool {
+    s.is_empty()
+}
+
+#[derive(Argument)]
+struct ServerSpec {
+    #[argument(skip)]
+    #[allow(dead_code)]
+    cache_key: u64,
+    #[argument(variant(named), default = 8080)]
+    port: u16,
+    #[argument(variant(named), default = "Level::Info")]
+    log_level: Level,
+    #[argument(variant(named), default = 4)]
+    workers: Option<u32>,
+    #[argument(variant(named), skip_if = "is_empty")]
+    name: String,
+    #[argument(skip_if = "Vec::is_empty")]
+    config: Vec<PathBuf>,
+}
+
+#[derive(Argument, Default)]
+#[argument(omit_defaults)]
+struct RsyncSpec<T> {
+    #[argument(variant(flag))]
+    archive: bool,
+    #[argument(variant(named))]
+    timeout: u32,
+    #[argument(variant(named), default = 3)]
+    retries: u32,
+    source: T,
+}
+
+#[derive(Argument)]
+#[allow(dead_code)]
+enum Action {
+    #[argument(variant(subcommand))]
+    Start(
+        #[argument(skip)] u8,
+        #[argument(variant(named = "-n"), default = 1)] u32,
+    ),
+}
+
+#[test]
+fn test_skip_and_field_defaults() {
+    let spec = ServerSpec {
+        cache_key: 42,
+        port: 8080,
+        log_level: Level::Info,
+        workers: Some(4),
+        name: String::new(),
+        config: vec![],
+    };
+    assert_eq!(args_of(&spec), Vec::<String>::new());
+
+    let spec = ServerSpec {
+        cache_key: 42,
+        port: 80,
+        log_level: Level::Warn,
+        workers: Some(2),
+        name: String::from("web"),
+        config: vec![PathBuf::from("a.toml")],
+    };
+    assert_eq!(
+        args_of(&spec),
+        [
+            "--port",
+            "80",
+            "--log-level",
+            "warn",
+            "--workers",
+            "2",
+            "--name",
+            "web",
+            "a.toml"
+        ]
+    );
+
+    assert_eq!(args_of(&Action::Start(0, 1)), ["start"]);
+    assert_eq!(args_of(&Action::Start(0, 2)), ["start", "-n", "2"]);
+}
+
+#[test]
+fn test_omit_defaults() {
+    let spec = RsyncSpec::<String>::default();
+    assert_eq!(args_of(&spec), ["--retries", "0"]);
+
+    let spec = RsyncSpec {
+        archive: true,
+        timeout: 30,
+        retries: 3,
+        source: String::from("src/"),
+    };
+    assert_eq!(args_of(&spec), ["--archive", "--timeout", "30", "src/"]);
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use common::args_of;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use unclap::{Argument, ArgumentReceiver};
+
+#[derive(Argument)]
+struct FfmpegSpec {
+    #[argument(variant(named = "-i"), order = 1)]
+    input: PathBuf,
+    output: PathBuf,
+    #[argument(variant(named = "-y"), order = -1)]
+    overwrite: Option<String>,
+    #[argument(variant(named = "-c:v"))]
+    codec: String,
+}
+
+#[derive(Argument)]
+#[argument(options_first)]
+struct GrepSpec {
+    pattern: String,
+    #[argument(last)]
+    files: Vec<PathBuf>,
+    #[argument(variant(flag = "-i"))]
+    ignore_case: bool,
+    #[argument(variant(named = "-m"))]
+    max_count: Option<u32>,
+}
+
+#[test]
+fn test_explicit_order() {
+    let spec = FfmpegSpec {
+        input: PathBuf::from("in.mkv"),
+        output: PathBuf::from("out.mp4"),
+        overwrite: Some(String::from("yes")),
+        codec: String::from("libx264"),
+    };
+    assert_eq!(
+        args_of(&spec),
+        ["-y", "yes", "out.mp4", "-c:v", "libx264", "-i", "in.mkv"]
+    );
+}
+
+#[test]
+fn test_options_first_and_last() {
+    let spec = GrepSpec {
+        pattern: String::from("-v"),
+        files: vec![PathBuf::from("-a.txt")],
+        ignore_case: true,
+        max_count: Some(1),
+    };
+    assert_eq!(args_of(&spec), ["-i", "-m", "1", "-v", "--", "-a.txt"]);
+}
+
+#[test]
+fn test_empty_last_field() {
+    let spec = GrepSpec {
+        pattern: String::from("todo"),
+        files: vec![],
+        ignore_case: true,
+        max_count: None,
+    };
+    assert_eq!(args_of(&spec), ["-i", "todo"]);
+}
+
+static RENDERED: AtomicUsize = AtomicUsize::new(0);
+
+fn render_files<R: ArgumentReceiver>(files: &[PathBuf], recv: &mut R) {
+    RENDERED.fetch_add(1, Ordering::SeqCst);
+    for file in files {
+        recv.one_arg(file.into());
+    }
+}
+
+#[derive(Argument)]
+struct RmSpec {
+    #[argument(variant(flag = "-f"))]
+    force: bool,
+    #[argument(last, with = "render_files")]
+    files: Vec<PathBuf>,
+}
+
+#[test]
+fn test_last_field_rendered_once() {
+    let spec = RmSpec {
+        force: true,
+        files: vec![PathBuf::from("-rf")],
+    };
+    assert_eq!(args_of(&spec), ["-f", "--", "-rf"]);
+    assert_eq!(RENDERED.load(Ordering::SeqCst), 1);
+}
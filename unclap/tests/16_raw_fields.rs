@@ -0,0 +1,64 @@
+mod common;
+
+use common::args_of;
+use std::ffi::OsString;
+use unclap::{Argument, ArgumentRecorder};
+
+#[derive(Argument)]
+#[allow(dead_code)]
+enum CargoCommand {
+    Build {
+        #[argument(variant(flag))]
+        release: bool,
+    },
+}
+
+#[derive(Argument)]
+struct CargoSpec {
+    #[argument(variant(named = "--config"))]
+    config: Option<String>,
+    #[argument(raw)]
+    extra_global: Vec<OsString>,
+    command: CargoCommand,
+    #[argument(raw)]
+    extra: Option<Vec<String>>,
+}
+
+#[test]
+fn test_raw_at_field_position() {
+    let spec = CargoSpec {
+        config: Some(String::from("net.offline=true")),
+        extra_global: vec![OsString::from("-Zunstable-options")],
+        command: CargoCommand::Build { release: true },
+        extra: Some(vec![String::from("--timings")]),
+    };
+    assert_eq!(
+        args_of(&spec),
+        [
+            "--config",
+            "net.offline=true",
+            "-Zunstable-options",
+            "build",
+            "--release",
+            "--timings"
+        ]
+    );
+}
+
+#[test]
+fn test_raw_arguments_are_untrusted() {
+    let spec = CargoSpec {
+        config: Some(String::from("net.offline=true")),
+        extra_global: vec![OsString::from("-Zunstable-options")],
+        command: CargoCommand::Build { release: true },
+        extra: Some(vec![String::from("--timings")]),
+    };
+    let mut recorder = ArgumentRecorder::new();
+    spec.append_to(&mut recorder);
+    let untrusted: Vec<_> = recorder.untrusted().collect();
+    assert_eq!(untrusted, ["-Zunstable-options", "--timings"]);
+    assert_eq!(recorder.recorded().len(), 6);
+    assert!(!recorder.recorded()[0].untrusted);
+    assert!(recorder.recorded()[2].untrusted);
+    assert_eq!(recorder.into_args()[3], "build");
+}
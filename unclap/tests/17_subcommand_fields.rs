@@ -0,0 +1,107 @@
+mod common;
+
+use common::args_of;
+use std::path::PathBuf;
+use unclap::Argument;
+
+#[derive(Argument)]
+#[allow(dead_code)]
+enum RemoteCommand {
+    Add {
+        name: String,
+        url: String,
+    },
+    #[argument(rename = "get-url")]
+    GetUrl {
+        #[argument(variant(flag))]
+        push: bool,
+        name: String,
+    },
+}
+
+#[derive(Argument)]
+#[allow(dead_code)]
+enum GitCommand {
+    Log {
+        #[argument(variant(flag))]
+        oneline: bool,
+    },
+    Remote {
+        #[argument(variant(flag = "-v"))]
+        verbose: bool,
+        #[argument(subcommand)]
+        command: Option<RemoteCommand>,
+    },
+}
+
+#[derive(Argument)]
+struct GitSpec {
+    #[argument(subcommand)]
+    command: GitCommand,
+    #[argument(variant(named = "-C"))]
+    directory: Option<PathBuf>,
+    #[argument(variant(flag))]
+    no_pager: bool,
+}
+
+#[derive(Argument)]
+struct CargoSpec {
+    #[argument(subcommand)]
+    command: GitCommand,
+    #[argument(format = "+{}")]
+    toolchain: String,
+}
+
+#[test]
+fn test_subcommand_after_global_options() {
+    let spec = GitSpec {
+        command: GitCommand::Log { oneline: true },
+        directory: Some(PathBuf::from("repo")),
+        no_pager: true,
+    };
+    assert_eq!(
+        args_of(&spec),
+        ["-C", "repo", "--no-pager", "log", "--oneline"]
+    );
+
+    let spec = CargoSpec {
+        command: GitCommand::Log { oneline: false },
+        toolchain: String::from("nightly"),
+    };
+    assert_eq!(args_of(&spec), ["+nightly", "log"]);
+}
+
+#[test]
+fn test_nested_subcommands() {
+    let spec = GitSpec {
+        command: GitCommand::Remote {
+            command: Some(RemoteCommand::Add {
+                name: String::from("origin"),
+                url: String::from("https://example.com/repo.git"),
+            }),
+            verbose: true,
+        },
+        directory: None,
+        no_pager: false,
+    };
+    assert_eq!(
+        args_of(&spec),
+        [
+            "remote",
+            "-v",
+            "add",
+            "origin",
+            "https://example.com/repo.git"
+        ]
+    );
+
+    let spec = GitSpec {
+        command: GitCommand::Remote {
+            command: None,
+            verbose: false,
+        },
+        directory: None,
+        no_pager: false,
+    };
+    assert_eq!(args_of(&spec), ["remote"]);
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use common::args_of;
+use unclap::Argument;
+
+#[derive(Argument)]
+struct Inner {
+    #[argument(variant(named))]
+    name: String,
+}
+
+#[derive(Argument)]
+#[allow(dead_code)]
+enum Command {
+    // The same names in different variants do not collide
+    Create {
+        #[argument(variant(named))]
+        name: String,
+        #[argument(variant(named))]
+        r#type: String,
+    },
+    Delete {
+        #[argument(variant(named))]
+        name: String,
+        #[argument(variant(flag(negatable)))]
+        force: Option<bool>,
+        #[argument(variant(flag = "--no-verify"))]
+        skip_checks: bool,
+    },
+}
+
+#[derive(Argument)]
+struct Spec {
+    #[argument(variant(named))]
+    name: String,
+    // Names of flattened arguments are only known at runtime
+    #[argument(flatten)]
+    inner: Inner,
+}
+
+#[test]
+fn test_distinct_flag_names() {
+    let create = Command::Create {
+        name: String::from("a"),
+        r#type: String::from("b"),
+    };
+    assert_eq!(args_of(&create), ["create", "--name", "a", "--type", "b"]);
+
+    let delete = Command::Delete {
+        name: String::from("a"),
+        force: Some(false),
+        skip_checks: true,
+    };
+    assert_eq!(
+        args_of(&delete),
+        ["delete", "--name", "a", "--no-force", "--no-verify"]
+    );
+
+    let spec = Spec {
+        name: String::from("outer"),
+        inner: Inner {
+            name: String::from("inner"),
+        },
+    };
+    assert_eq!(args_of(&spec), ["--name", "outer", "--name", "inner"]);
+}
+
+#[test]
+fn test_diagnostics() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use common::args_of;
+
+// Only the derive macro is imported, the generated code must not rely on any trait in scope
+mod plain {
+    use unclap::{Argument, ArgumentValue};
+
+    #[derive(ArgumentValue)]
+    pub enum Mode {
+        Fast,
+        Slow,
+    }
+
+    #[derive(Argument)]
+    pub struct Spec {
+        #[argument(variant(named(style = "equals")))]
+        pub mode: Mode,
+        #[argument(variant(flag))]
+        pub verbose: bool,
+        #[argument(variant(count = "-q"))]
+        pub quiet: u8,
+        #[argument(last)]
+        pub recv: Vec<String>,
+    }
+}
+
+mod reexport {
+    pub use unclap as cli;
+}
+
+mod renamed {
+    use crate::reexport::cli::{Argument, ArgumentValue};
+
+    #[derive(ArgumentValue)]
+    #[argument(crate = "crate::reexport::cli")]
+    pub enum Color {
+        Red,
+    }
+
+    #[derive(Argument)]
+    #[argument(crate = "crate::reexport::cli")]
+    pub struct Spec {
+        #[argument(variant(named))]
+        pub color: Color,
+        #[argument(flatten)]
+        pub inner: super::plain::Spec,
+    }
+}
+
+#[test]
+fn test_derived_without_traits_in_scope() {
+    let spec = plain::Spec {
+        mode: plain::Mode::Slow,
+        verbose: true,
+        quiet: 2,
+        recv: vec![String::from("file")],
+    };
+    assert_eq!(
+        args_of(&spec),
+        ["--mode=slow", "--verbose", "-q", "-q", "--", "file"]
+    );
+}
+
+#[test]
+fn test_crate_path_override() {
+    let spec = renamed::Spec {
+        color: renamed::Color::Red,
+        inner: plain::Spec {
+            mode: plain::Mode::Fast,
+            verbose: false,
+            quiet: 0,
+            recv: vec![],
+        },
+    };
+    assert_eq!(args_of(&spec), ["--color", "red", "--mode=fast"]);
+}
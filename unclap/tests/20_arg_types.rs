@@ -0,0 +1,99 @@
+use std::path::PathBuf;
+use unclap::{Argument, ArgumentValue};
+
+#[derive(ArgumentValue)]
+enum Level {
+    Low,
+    High,
+}
+
+#[derive(Argument)]
+#[argument(arg_type = "String")]
+struct Common {
+    #[argument(variant(flag))]
+    dry_run: bool,
+}
+
+#[derive(Argument)]
+#[argument(arg_type = "String")]
+struct Job {
+    #[argument(variant(named(style = "equals")))]
+    level: Level,
+    #[argument(variant(flag))]
+    verbose: bool,
+    #[argument(variant(count = "-q"))]
+    quiet: u8,
+    #[argument(variant(named(multiple(delimited = ","))))]
+    tags: Vec<String>,
+    #[argument(variant(named), format = "{}s")]
+    timeout: Option<u32>,
+    #[argument(flatten)]
+    common: Common,
+    image: String,
+}
+
+#[test]
+fn test_string_arguments() {
+    let job = Job {
+        level: Level::High,
+        verbose: true,
+        quiet: 2,
+        tags: vec!["a".into(), "b".into()],
+        timeout: Some(30),
+        common: Common { dry_run: true },
+        image: "alpine".into(),
+    };
+    let mut args: Vec<String> = Vec::new();
+    job.append_to(&mut args);
+    assert_eq!(
+        args,
+        [
+            "--level=high",
+            "--verbose",
+            "-q",
+            "-q",
+            "--tags",
+            "a,b",
+            "--timeout",
+            "30s",
+            "--dry-run",
+            "alpine",
+        ]
+    );
+
+    let job = Job {
+        level: Level::Low,
+        verbose: false,
+        quiet: 0,
+        tags: vec![],
+        timeout: None,
+        common: Common { dry_run: false },
+        image: "busybox".into(),
+    };
+    let mut args: Vec<String> = Vec::new();
+    job.append_to(&mut args);
+    assert_eq!(args, ["--level=low", "busybox"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_byte_arguments() {
+    use std::ffi::OsString;
+    use std::os::unix::ffi::OsStringExt;
+
+    #[derive(Argument)]
+    #[argument(arg_type = "Vec<u8>")]
+    struct Copy {
+        #[argument(variant(named(name = "--to", style = "equals")))]
+        target: PathBuf,
+        source: OsString,
+    }
+
+    let copy = Copy {
+        target: PathBuf::from("/tmp/out"),
+        source: OsString::from_vec(vec![b'f', 0xff, b'o']),
+    };
+    let mut args: Vec<Vec<u8>> = Vec::new();
+    copy.append_to(&mut args);
+    assert_eq!(args, [b"--to=/tmp/out".to_vec(), vec![b'f', 0xff, b'o']]);
+}
@@ -0,0 +1,129 @@
+mod common;
+
+use common::args_of;
+use std::ffi::{OsStr, OsString};
+use std::path::PathBuf;
+use unclap::{Argument, ArgumentExt, ArgumentRecorder};
+
+#[derive(Argument)]
+struct Build {
+    #[argument(variant(flag))]
+    release: bool,
+    #[argument(env = "CARGO_TARGET_DIR")]
+    target_dir: Option<PathBuf>,
+    #[argument(env = "RUSTFLAGS")]
+    rustflags: Vec<String>,
+    #[argument(env = "CARGO_BUILD_JOBS")]
+    jobs: u32,
+}
+
+/// The changed variables as `KEY=value`, and removed variables as `KEY`
+fn envs_of(spec: &impl Argument) -> Vec<String> {
+    let cmd = spec.to_command("prog");
+    let mut envs: Vec<String> = cmd
+        .get_envs()
+        .map(|(key, value)| match value {
+            Some(value) => format!("{}={}", key.to_str().unwrap(), value.to_str().unwrap()),
+            None => key.to_str().unwrap().to_owned(),
+        })
+        .collect();
+    envs.sort();
+    envs
+}
+
+#[test]
+fn test_env_fields() {
+    let build = Build {
+        release: true,
+        target_dir: Some(PathBuf::from("/tmp/target")),
+        rustflags: vec!["-C".into(), "opt-level=3".into()],
+        jobs: 4,
+    };
+    assert_eq!(args_of(&build), ["--release"]);
+    assert_eq!(
+        envs_of(&build),
+        [
+            "CARGO_BUILD_JOBS=4",
+            "CARGO_TARGET_DIR=/tmp/target",
+            "RUSTFLAGS=-C opt-level=3",
+        ]
+    );
+}
+
+#[test]
+fn test_absent_env_fields() {
+    let build = Build {
+        release: false,
+        target_dir: None,
+        rustflags: vec![],
+        jobs: 1,
+    };
+    assert_eq!(args_of(&build), Vec::<String>::new());
+    // An absent optional field and an empty value leave the variable untouched
+    assert_eq!(envs_of(&build), ["CARGO_BUILD_JOBS=1"]);
+}
+
+#[derive(Argument)]
+struct Check {
+    #[argument(env(name = "RUSTFLAGS", remove_empty))]
+    rustflags: Vec<String>,
+    #[argument(env(name = "CARGO_TARGET_DIR", remove_empty))]
+    target_dir: Option<PathBuf>,
+}
+
+#[test]
+fn test_removed_env_fields() {
+    let check = Check {
+        rustflags: vec![],
+        target_dir: None,
+    };
+    assert_eq!(envs_of(&check), ["CARGO_TARGET_DIR", "RUSTFLAGS"]);
+
+    let check = Check {
+        rustflags: vec!["-Dwarnings".into()],
+        target_dir: Some(PathBuf::from("target")),
+    };
+    assert_eq!(
+        envs_of(&check),
+        ["CARGO_TARGET_DIR=target", "RUSTFLAGS=-Dwarnings"]
+    );
+}
+
+#[test]
+fn test_recorded_env_fields() {
+    let build = Build {
+        release: true,
+        target_dir: None,
+        rustflags: vec!["-Dwarnings".into()],
+        jobs: 2,
+    };
+    let mut recorder = ArgumentRecorder::new();
+    build.append_to(&mut recorder);
+    assert_eq!(
+        recorder.environment(),
+        [
+            (
+                OsString::from("RUSTFLAGS"),
+                Some(OsString::from("-Dwarnings"))
+            ),
+            (
+                OsString::from("CARGO_BUILD_JOBS"),
+                Some(OsString::from("2"))
+            ),
+        ]
+    );
+    assert_eq!(recorder.into_args(), [OsStr::new("--release")]);
+}
+
+#[test]
+fn test_env_fields_without_an_environment() {
+    let build = Build {
+        release: true,
+        target_dir: None,
+        rustflags: vec!["-Dwarnings".into()],
+        jobs: 1,
+    };
+    let mut args: Vec<OsString> = Vec::new();
+    build.append_to(&mut args);
+    assert_eq!(args, [OsStr::new("--release")]);
+}
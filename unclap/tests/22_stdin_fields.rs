@@ -0,0 +1,105 @@
+mod common;
+
+use common::args_of;
+use std::ffi::{OsStr, OsString};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use unclap::{Argument, ArgumentExt, ArgumentRecorder, CommandExt, StdinPayload};
+
+#[derive(Argument)]
+struct Apply {
+    #[argument(variant(named = "-f"))]
+    file: String,
+    #[argument(stdin)]
+    manifest: String,
+}
+
+#[derive(Argument)]
+struct Cat {
+    #[argument(stdin)]
+    input: Option<PathBuf>,
+}
+
+#[derive(Argument)]
+struct Echo {
+    #[argument(stdin)]
+    text: Vec<u8>,
+}
+
+#[test]
+fn test_stdin_payload() {
+    let apply = Apply {
+        file: "-".into(),
+        manifest: "kind: Pod\n".into(),
+    };
+    assert_eq!(args_of(&apply), ["-f", "-"]);
+    let invocation = apply.to_command("kubectl");
+    assert_eq!(
+        invocation.stdin_payload(),
+        Some(&StdinPayload::Bytes(b"kind: Pod\n".to_vec()))
+    );
+
+    let mut recorder = ArgumentRecorder::new();
+    apply.append_to(&mut recorder);
+    assert_eq!(recorder.stdin(), invocation.stdin_payload());
+
+    let cat = Cat { input: None };
+    assert_eq!(cat.to_command("cat").stdin_payload(), None);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_command_stdin_without_a_payload() {
+    let cat = Cat { input: None };
+    let output = cat.to_command("cat").stdin(Stdio::null()).output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"");
+}
+
+#[test]
+fn test_stdin_fields_without_an_invocation() {
+    let apply = Apply {
+        file: "-".into(),
+        manifest: "kind: Pod\n".into(),
+    };
+    let mut args: Vec<OsString> = Vec::new();
+    apply.append_to(&mut args);
+    assert_eq!(args, [OsStr::new("-f"), OsStr::new("-")]);
+
+    let mut cmd = Command::new("kubectl");
+    cmd.extend(&apply);
+    assert_eq!(cmd.get_args().collect::<Vec<_>>(), ["-f", "-"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_stdin_is_fed_to_the_program() {
+    let echo = Echo {
+        text: b"kind: Pod\n".to_vec(),
+    };
+    let output = echo.to_command("cat").output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"kind: Pod\n");
+
+    let path = std::env::temp_dir().join(format!("unclap-stdin-{}", std::process::id()));
+    std::fs::write(&path, "from a file").unwrap();
+    let cat = Cat {
+        input: Some(path.clone()),
+    };
+    let output = cat.to_command("cat").output();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(output.unwrap().stdout, b"from a file");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_redirected_output_is_kept() {
+    let echo = Echo {
+        text: b"kind: Pod\n".to_vec(),
+    };
+    let mut invocation = echo.to_command("cat");
+    invocation.stdout(Stdio::null());
+    let output = invocation.output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"");
+}
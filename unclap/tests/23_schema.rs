@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use unclap::{Argument, ArgumentSchema, FieldInfo, FieldKind, Schema, VariantInfo, VariantKind};
+
+/// Connection settings
+#[derive(Argument)]
+struct Connection {
+    /// The host to connect to
+    #[argument(variant(named))]
+    host: String,
+}
+
+/// Runs a container
+///
+/// Like `docker run`.
+#[derive(Argument)]
+struct Run {
+    /// Remove the container when it exits
+    #[argument(variant(flag))]
+    rm: bool,
+    #[argument(variant(count = "-v"))]
+    verbose: u8,
+    /// Environment variables of the container
+    #[argument(variant(named(name = "-e", key_value = "=", multiple = "repeat")))]
+    env: BTreeMap<String, String>,
+    #[argument(variant(named = "-w"))]
+    workdir: Option<PathBuf>,
+    #[argument(flatten(prefix = "docker-"))]
+    connection: Connection,
+    #[argument(env = "DOCKER_CONFIG")]
+    config: Option<PathBuf>,
+    #[argument(skip)]
+    #[allow(dead_code)]
+    internal: u32,
+    image: String,
+    #[argument(last)]
+    args: Vec<String>,
+}
+
+#[derive(Argument)]
+#[allow(dead_code)]
+enum Remote {
+    /// Adds a remote
+    Add { name: String, url: String },
+    #[argument(variant(named = "--prune"))]
+    Prune(String),
+}
+
+fn field(
+    name: &'static str,
+    flag: Option<&'static str>,
+    kind: FieldKind,
+    value_type: &'static str,
+) -> FieldInfo {
+    FieldInfo {
+        name,
+        flag,
+        kind,
+        value_type,
+        optional: false,
+        repeated: false,
+        doc: None,
+        flattened: None,
+    }
+}
+
+#[test]
+fn test_struct_schema() {
+    let schema = Run::schema();
+    assert_eq!(schema.doc, Some("Runs a container\n\nLike `docker run`."));
+    assert!(schema.variants.is_empty());
+    assert_eq!(
+        schema.fields,
+        [
+            FieldInfo {
+                doc: Some("Remove the container when it exits"),
+                ..field("rm", Some("--rm"), FieldKind::Flag, "bool")
+            },
+            field("verbose", Some("-v"), FieldKind::Count, "u8"),
+            FieldInfo {
+                repeated: true,
+                doc: Some("Environment variables of the container"),
+                ..field(
+                    "env",
+                    Some("-e"),
+                    FieldKind::Named,
+                    "BTreeMap<String, String>"
+                )
+            },
+            FieldInfo {
+                optional: true,
+                ..field("workdir", Some("-w"), FieldKind::Named, "PathBuf")
+            },
+            FieldInfo {
+                flattened: Some(Connection::schema()),
+                ..field(
+                    "connection",
+                    Some("docker-"),
+                    FieldKind::Flatten,
+                    "Connection"
+                )
+            },
+            FieldInfo {
+                optional: true,
+                ..field("config", Some("DOCKER_CONFIG"), FieldKind::Env, "PathBuf")
+            },
+            field("image", None, FieldKind::Positional, "String"),
+            FieldInfo {
+                repeated: true,
+                ..field("args", None, FieldKind::Positional, "Vec<String>")
+            },
+        ]
+    );
+    assert_eq!(
+        Connection::schema(),
+        Schema {
+            doc: Some("Connection settings"),
+            fields: vec![FieldInfo {
+                doc: Some("The host to connect to"),
+                ..field("host", Some("--host"), FieldKind::Named, "String")
+            }],
+            variants: vec![],
+        }
+    );
+}
+
+#[test]
+fn test_enum_schema() {
+    let schema = Remote::schema();
+    assert!(schema.fields.is_empty());
+    assert_eq!(
+        schema.variants,
+        [
+            VariantInfo {
+                name: "Add",
+                flag: Some("add"),
+                kind: VariantKind::Subcommand,
+                doc: Some("Adds a remote"),
+                fields: vec![
+                    field("name", None, FieldKind::Positional, "String"),
+                    field("url", None, FieldKind::Positional, "String"),
+                ],
+            },
+            VariantInfo {
+                name: "Prune",
+                flag: Some("--prune"),
+                kind: VariantKind::Named,
+                doc: None,
+                fields: vec![field("0", None, FieldKind::Positional, "String")],
+            },
+        ]
+    );
+}
+
+#[derive(Argument)]
+struct Git {
+    #[argument(env(name = "GIT_TRACE_FLAGS"))]
+    trace: Vec<String>,
+    #[argument(stdin)]
+    input: Vec<u8>,
+    #[argument(subcommand)]
+    remote: Remote,
+}
+
+#[test]
+fn test_subcommand_schema() {
+    assert_eq!(
+        Git::schema().fields,
+        [
+            field(
+                "trace",
+                Some("GIT_TRACE_FLAGS"),
+                FieldKind::Env,
+                "Vec<String>"
+            ),
+            field("input", None, FieldKind::Stdin, "Vec<u8>"),
+            FieldInfo {
+                flattened: Some(Remote::schema()),
+                ..field("remote", None, FieldKind::Subcommand, "Remote")
+            },
+        ]
+    );
+}
@@ -0,0 +1,136 @@
+mod common;
+
+use common::command_args;
+use unclap::{Argument, ArgumentExt, UnsupportedArgument, Version};
+
+#[derive(Argument)]
+struct GitClone {
+    #[argument(variant(flag), since = "2.28")]
+    no_tags: bool,
+    #[argument(variant(named), since = "1.9", until = "3.0")]
+    depth: Option<u32>,
+    #[argument(variant(flag), since = "2.23", fallback = "--reference-if-able")]
+    also_filter_submodules: bool,
+    #[argument(variant(named), since = "2.9", unsupported = "skip")]
+    jobs: Option<u32>,
+    url: String,
+}
+
+#[test]
+fn test_supported_versions() {
+    let all = [
+        "--no-tags",
+        "--depth",
+        "1",
+        "--also-filter-submodules",
+        "--jobs",
+        "4",
+        "https://example.com/repo.git",
+    ];
+    let spec = GitClone {
+        no_tags: true,
+        depth: Some(1),
+        also_filter_submodules: true,
+        jobs: Some(4),
+        url: "https://example.com/repo.git".into(),
+    };
+    // Without a target version, all fields are passed
+    assert_eq!(command_args(&spec.to_command("git")), all);
+    let cmd = spec
+        .to_versioned_command("git", Version::new(&[2, 40]))
+        .unwrap();
+    assert_eq!(command_args(&cmd), all);
+}
+
+#[test]
+fn test_fallback_and_skip() {
+    let spec = GitClone {
+        no_tags: false,
+        depth: Some(1),
+        also_filter_submodules: true,
+        jobs: Some(4),
+        url: "https://example.com/repo.git".into(),
+    };
+    let cmd = spec
+        .to_versioned_command("git", "2.7.4".parse().unwrap())
+        .unwrap();
+    assert_eq!(
+        command_args(&cmd),
+        [
+            "--depth",
+            "1",
+            "--reference-if-able",
+            "https://example.com/repo.git"
+        ]
+    );
+}
+
+#[test]
+fn test_unsupported_fields() {
+    let spec = GitClone {
+        no_tags: true,
+        depth: Some(1),
+        also_filter_submodules: true,
+        jobs: Some(4),
+        url: "https://example.com/repo.git".into(),
+    };
+    let error = spec
+        .to_versioned_command("git", "2.7.4".parse().unwrap())
+        .unwrap_err();
+    assert_eq!(
+        error,
+        UnsupportedArgument {
+            name: String::from("--no-tags"),
+            since: Some(Version::new(&[2, 28])),
+            until: None,
+            version: Version::new(&[2, 7, 4]),
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "`--no-tags` is supported from version 2.28, but the target version is 2.7.4"
+    );
+
+    let error = spec
+        .to_versioned_command("git", Version::new(&[3]))
+        .unwrap_err();
+    assert_eq!(error.name, "--depth");
+
+    // Unset fields are not reported
+    let spec = GitClone {
+        depth: None,
+        ..spec
+    };
+    assert!(spec.to_versioned_command("git", Version::new(&[3])).is_ok());
+}
+
+#[derive(Argument)]
+struct Submodule {
+    #[argument(flatten(prefix = "clone-"))]
+    clone: GitClone,
+}
+
+#[test]
+fn test_unsupported_flattened_fields() {
+    let spec = Submodule {
+        clone: GitClone {
+            no_tags: false,
+            depth: Some(1),
+            also_filter_submodules: false,
+            jobs: None,
+            url: "https://example.com/repo.git".into(),
+        },
+    };
+    let error = spec
+        .to_versioned_command("git", Version::new(&[3]))
+        .unwrap_err();
+    assert_eq!(error.name, "--clone-depth");
+}
+
+#[test]
+fn test_version_order() {
+    assert_eq!(Version::new(&[2, 28]), "2.28.0".parse().unwrap());
+    assert!(Version::new(&[2, 9]) < Version::new(&[2, 28]));
+    assert!("2.x".parse::<Version>().is_err());
+    assert_eq!(Version::new(&[2, 28, 1]).to_string(), "2.28.1");
+}
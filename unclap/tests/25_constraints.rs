@@ -0,0 +1,216 @@
+use std::path::PathBuf;
+use unclap::{Argument, Validate, ValidationError, Violation};
+
+#[derive(Argument)]
+struct Auth {
+    #[argument(variant(named), requires = "password")]
+    user: Option<String>,
+    #[argument(variant(named))]
+    password: Option<String>,
+}
+
+#[derive(Argument)]
+struct Fetch {
+    #[argument(variant(flag), conflicts_with = "verbose")]
+    quiet: bool,
+    #[argument(variant(count = "-v"))]
+    verbose: u8,
+    #[argument(variant(named), range(min = 1, max = 64))]
+    jobs: Option<u32>,
+    #[argument(variant(named), one_of = "source")]
+    url: Option<String>,
+    #[argument(variant(named), one_of = "source")]
+    bundle: Option<PathBuf>,
+    #[argument(flatten)]
+    auth: Auth,
+    #[argument(non_empty)]
+    refspecs: Vec<String>,
+}
+
+fn error(field: &'static str, violation: Violation) -> ValidationError {
+    ValidationError { field, violation }
+}
+
+#[test]
+fn test_valid_spec() {
+    let spec = Fetch {
+        quiet: false,
+        verbose: 1,
+        jobs: Some(4),
+        url: Some("https://example.com/repo.git".into()),
+        bundle: None,
+        auth: Auth {
+            user: None,
+            password: None,
+        },
+        refspecs: vec!["main".into()],
+    };
+    assert_eq!(spec.validate(), Ok(()));
+    let cmd = spec.to_checked_command("git").unwrap();
+    assert_eq!(cmd.get_args().count(), 6);
+}
+
+#[test]
+fn test_field_constraints() {
+    let spec = Fetch {
+        quiet: true,
+        verbose: 1,
+        jobs: Some(0),
+        url: Some("https://example.com/repo.git".into()),
+        bundle: Some(PathBuf::from("repo.bundle")),
+        auth: Auth {
+            user: Some("me".into()),
+            password: None,
+        },
+        refspecs: vec![],
+    };
+    let errors = spec.to_checked_command("git").unwrap_err();
+    assert_eq!(
+        errors.errors(),
+        [
+            error("quiet", Violation::ConflictsWith("verbose")),
+            error(
+                "jobs",
+                Violation::OutOfRange {
+                    min: Some("1"),
+                    max: Some("64"),
+                }
+            ),
+            error("user", Violation::Requires("password")),
+            error("refspecs", Violation::Empty),
+            error("source", Violation::OneOf(vec!["url", "bundle"])),
+        ]
+    );
+    assert_eq!(
+        errors.errors()[0].to_string(),
+        "`quiet` can not be used with `verbose`"
+    );
+    assert_eq!(
+        errors.errors()[4].to_string(),
+        "Only one field of the group `source` can be set, but `url`, `bundle` are set"
+    );
+}
+
+#[test]
+fn test_one_of_requires_a_field() {
+    let spec = Fetch {
+        quiet: false,
+        verbose: 1,
+        jobs: None,
+        url: None,
+        bundle: None,
+        auth: Auth {
+            user: None,
+            password: None,
+        },
+        refspecs: vec!["main".into()],
+    };
+    let errors = spec.validate().unwrap_err();
+    assert_eq!(
+        errors.into_errors(),
+        [error("source", Violation::OneOf(vec![]))]
+    );
+}
+
+#[derive(Argument)]
+enum Remote {
+    Add {
+        #[argument(non_empty)]
+        name: String,
+        url: String,
+    },
+}
+
+#[test]
+fn test_variant_constraints() {
+    let add = Remote::Add {
+        name: String::new(),
+        url: "https://example.com".into(),
+    };
+    let mut args: Vec<std::ffi::OsString> = Vec::new();
+    let errors = add.append_checked(&mut args).unwrap_err();
+    assert_eq!(errors.errors(), [error("name", Violation::Empty)]);
+    assert!(args.is_empty());
+}
+
+#[derive(Argument)]
+struct Git {
+    #[argument(variant(flag))]
+    verbose: bool,
+    #[argument(subcommand)]
+    command: Remote,
+}
+
+#[test]
+fn test_subcommand_constraints() {
+    let git = Git {
+        verbose: true,
+        command: Remote::Add {
+            name: String::new(),
+            url: "https://example.com".into(),
+        },
+    };
+    let errors = git.to_checked_command("git").unwrap_err();
+    assert_eq!(errors.errors(), [error("name", Violation::Empty)]);
+}
+
+#[derive(Argument)]
+struct Grep {
+    #[argument(variant(named = "-f"), requires = "files")]
+    pattern_file: Option<PathBuf>,
+    #[argument(variant(named = "-C"), range(min = "-1", max = 10))]
+    context: Option<i32>,
+    #[argument(last)]
+    files: Vec<PathBuf>,
+}
+
+#[test]
+fn test_last_field_constraints() {
+    let grep = Grep {
+        pattern_file: Some(PathBuf::from("patterns")),
+        context: Some(-2),
+        files: vec![],
+    };
+    assert_eq!(
+        grep.validate().unwrap_err().errors(),
+        [
+            error("pattern_file", Violation::Requires("files")),
+            error(
+                "context",
+                Violation::OutOfRange {
+                    min: Some("-1"),
+                    max: Some("10"),
+                }
+            ),
+        ]
+    );
+
+    let grep = Grep {
+        pattern_file: Some(PathBuf::from("patterns")),
+        context: None,
+        files: vec![PathBuf::from("src")],
+    };
+    assert_eq!(grep.validate(), Ok(()));
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_regex_constraint() {
+    #[derive(Argument)]
+    struct Tag {
+        #[argument(regex = "^v[0-9]+\\.[0-9]+$")]
+        name: String,
+    }
+
+    let tag = Tag {
+        name: "v1.2".into(),
+    };
+    assert_eq!(tag.validate(), Ok(()));
+    let tag = Tag {
+        name: "latest".into(),
+    };
+    assert_eq!(
+        tag.validate().unwrap_err().errors(),
+        [error("name", Violation::Mismatch("^v[0-9]+\\.[0-9]+$"))]
+    );
+}
@@ -0,0 +1,17 @@
+//! Helpers shared by the integration tests
+#![allow(dead_code)]
+
+use std::process::Command;
+use unclap::{Argument, ArgumentExt};
+
+/// The arguments of a command, as strings
+pub fn command_args(cmd: &Command) -> Vec<String> {
+    cmd.get_args()
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect()
+}
+
+/// The arguments a spec passes to a program
+pub fn args_of<A: Argument>(spec: &A) -> Vec<String> {
+    command_args(&spec.to_command("prog"))
+}
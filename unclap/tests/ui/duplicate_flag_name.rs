@@ -0,0 +1,11 @@
+use unclap::Argument;
+
+#[derive(Argument)]
+struct Spec {
+    #[argument(variant(flag))]
+    dry_run: bool,
+    #[argument(variant(flag = "--dry-run"))]
+    simulate: bool,
+}
+
+fn main() {}
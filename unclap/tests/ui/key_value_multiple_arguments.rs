@@ -0,0 +1,10 @@
+use std::collections::BTreeMap;
+use unclap::Argument;
+
+#[derive(Argument)]
+struct Spec {
+    #[argument(variant(named(name = "-D", key_value = "=")))]
+    defines: BTreeMap<String, Vec<String>>,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use std::path::PathBuf;
+use unclap::Argument;
+
+#[derive(Argument)]
+struct Spec {
+    #[argument(last)]
+    files: Vec<PathBuf>,
+    #[argument(last)]
+    patterns: Vec<String>,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use unclap::Argument;
+
+#[derive(Argument)]
+struct Spec {
+    #[argument(variant(flag(negatable)))]
+    color: Option<bool>,
+    #[argument(variant(flag))]
+    no_color: bool,
+}
+
+fn main() {}
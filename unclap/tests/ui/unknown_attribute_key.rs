@@ -0,0 +1,9 @@
+use unclap::Argument;
+
+#[derive(Argument)]
+struct Spec {
+    #[argument(variant(named), skip_iff = "String::is_empty")]
+    name: String,
+}
+
+fn main() {}
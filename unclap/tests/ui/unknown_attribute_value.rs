@@ -0,0 +1,11 @@
+use unclap::Argument;
+
+#[derive(Argument)]
+struct Spec {
+    #[argument(variant(named(style = "equal")))]
+    color: String,
+    #[argument(variant(named(multiple = "repaet")))]
+    include: Vec<String>,
+}
+
+fn main() {}